@@ -1,8 +1,18 @@
-use crate::intent::types::{Intent, OperationType};
+use crate::command_builder::command::FfmpegCommand;
+use crate::command_builder::filtergraph::{Chain, Filter, Filtergraph};
+use crate::executor::media_info::MediaInfo;
+use crate::intent::params::{ConcatMethod, FrameRate, GifOptions, Pixels, Quality, Resolution, TimeRange, TimeSpan, TrimMode};
+use crate::intent::types::{Intent, Operation};
+use crate::utils::codecs::{self, Codec, CodecKind};
+use crate::utils::containers;
+use crate::utils::file_utils;
+use crate::utils::time::format_timestamp;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 
 /// Command builder for converting intents into ffmpeg commands.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct CommandBuilder;
 
 impl CommandBuilder {
@@ -19,8 +29,8 @@ impl CommandBuilder {
     ///
     /// # Returns
     ///
-    /// A `Result` containing either the ffmpeg command string or an error.
-    pub fn build_command(&self, intent: &Intent) -> Result<String, Box<dyn std::error::Error>> {
+    /// A `Result` containing either the ffmpeg command or an error.
+    pub fn build_command(&self, intent: &Intent) -> Result<FfmpegCommand, Box<dyn std::error::Error>> {
         self.build_command_with_output_path(intent, intent.output_path.clone())
     }
 
@@ -33,69 +43,1154 @@ impl CommandBuilder {
     ///
     /// # Returns
     ///
-    /// A `Result` containing either the ffmpeg command string or an error.
-    pub fn build_command_with_output_path(&self, intent: &Intent, output_path: PathBuf) -> Result<String, Box<dyn std::error::Error>> {
-        let input_path = intent.input_path.to_string_lossy();
-        let output_path = output_path.to_string_lossy();
+    /// A `Result` containing either the ffmpeg command or an error. It is an
+    /// error for the output to be one of the inputs, even through a symlink.
+    pub fn build_command_with_output_path(&self, intent: &Intent, output_path: PathBuf) -> Result<FfmpegCommand, Box<dyn std::error::Error>> {
+        if intent.operation != Operation::Inspect {
+            if let Some(input) = intent.input_paths.iter().find(|input| file_utils::is_same_file(input, &output_path)) {
+                return Err(format!(
+                    "The output {} is the input file {}; ffmpeg would overwrite it while reading it. Choose another output name",
+                    output_path.display(),
+                    input.display()
+                ).into());
+            }
+        }
 
-        let cmd = match &intent.operation {
-            OperationType::Convert => {
-                format!("ffmpeg -i \"{}\" \"{}\"", input_path, output_path)
+        if let Operation::Concat(method) = intent.operation {
+            return self.build_concat(intent, method, output_path);
+        }
+
+        let input_path = intent.input_path().ok_or("Intent has no input file")?;
+
+        if intent.operation == Operation::Inspect {
+            return Ok(MediaInfo::probe_command(input_path));
+        }
+        let mut input_options = Options::default();
+        let mut output_options = Options::default();
+
+        let makes_gif = output_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gif"));
+
+        match &intent.operation {
+            Operation::Gif(options) => {
+                self.gif_options(intent, options, &mut input_options, &mut output_options);
+            },
+            Operation::Convert if makes_gif => {
+                self.gif_options(intent, &GifOptions::default(), &mut input_options, &mut output_options);
+            },
+            Operation::Convert => {},
+            Operation::Resize(resolution) => {
+                output_options.push(
+                    Filtergraph::from(Chain::new().filter(scale_filter(resolution))).video_args(),
+                    defaulted(describe_scale(resolution), intent.source("size")),
+                    intent.source("size"),
+                );
+            },
+            Operation::Transcode { video, audio } => {
+                self.transcode_options(intent, *video, *audio, &output_path, &mut output_options)?;
+            },
+            Operation::ExtractAudio(quality) => {
+                self.audio_options(intent, *quality, &output_path, &mut output_options)?;
+            },
+            Operation::Trim { range, mode } => {
+                self.trim_options(intent, range, *mode, true, &mut input_options, &mut output_options);
             },
-            OperationType::Resize => {
-                let width = intent.parameters.get("width").unwrap_or(&"1920".to_string()).clone();
-                let height = intent.parameters.get("height").unwrap_or(&"1080".to_string()).clone();
-                format!("ffmpeg -i \"{}\" -vf scale={}:{} \"{}\"", input_path, width, height, output_path)
+            Operation::Thumbnail { time } => {
+                match time {
+                    Some(time) => input_options.push(
+                        ["-ss".to_string(), format_timestamp(time.seconds())],
+                        format!("jump to {} before taking the frame", format_timestamp(time.seconds())),
+                        intent.source("time"),
+                    ),
+                    None => output_options.push(
+                        Filtergraph::from(Chain::new().filter(Filter::new("thumbnail"))).video_args(),
+                        "let ffmpeg pick a representative frame",
+                        None,
+                    ),
+                }
+                output_options.push(["-frames:v", "1"], "save a single frame", intent.source("operation"));
             },
-            OperationType::Transcode => {
-                let video_codec = intent.parameters.get("vcodec").unwrap_or(&"libx264".to_string()).clone();
-                let audio_codec = intent.parameters.get("acodec").unwrap_or(&"aac".to_string()).clone();
-                format!("ffmpeg -i \"{}\" -c:v {} -c:a {} \"{}\"", input_path, video_codec, audio_codec, output_path)
+            Operation::ExtractFrames(fps) => {
+                output_options.push(
+                    Filtergraph::from(Chain::new().filter(Filter::new("fps").arg(fps.filter_value()))).video_args(),
+                    defaulted(format!("save {}", fps), intent.source("fps")),
+                    intent.source("fps"),
+                );
             },
-            OperationType::ExtractAudio => {
-                format!("ffmpeg -i \"{}\" -q:a 0 -map a \"{}\"", input_path, output_path)
+            Operation::Pipeline(steps) => {
+                self.pipeline_options(intent, steps, &output_path, &mut input_options, &mut output_options)?;
             },
+            Operation::Concat(_) | Operation::Inspect => unreachable!("built separately above"),
+        }
+
+        let output_reason = match intent.source("output") {
+            Some(_) => "write the result to this file",
+            None => "write the result to this file, named after the input",
         };
 
+        let mut cmd = FfmpegCommand::new("ffmpeg");
+        input_options.append_to(&mut cmd);
+        cmd.arg("-i").arg(input_path).explain(2, "read the input file", intent.source("input"));
+        output_options.append_to(&mut cmd);
+        cmd.output(output_path).explain(1, output_reason, intent.source("output"));
+
         Ok(cmd)
     }
+
+    /// Builds a command joining all inputs of the intent in order.
+    ///
+    /// With the demuxer method, the inputs are listed in a support file for
+    /// the concat demuxer and their streams are copied, which only works when
+    /// all inputs share codecs and stream layout. Otherwise the concat filter
+    /// decodes and re-encodes everything, joining only the kinds of stream
+    /// that every input has, and only the audio for audio-only formats.
+    fn build_concat(
+        &self,
+        intent: &Intent,
+        method: ConcatMethod,
+        output_path: PathBuf,
+    ) -> Result<FfmpegCommand, Box<dyn std::error::Error>> {
+        if intent.input_paths.len() < 2 {
+            return Err("Joining needs at least two input files".into());
+        }
+
+        let mut cmd = FfmpegCommand::new("ffmpeg");
+        let count = intent.input_paths.len();
+
+        match method {
+            ConcatMethod::Demuxer => {
+                let mut list = String::new();
+                for input in &intent.input_paths {
+                    let input = std::path::absolute(input)?;
+                    list.push_str(&format!("file '{}'\n", input.to_string_lossy().replace('\'', "'\\''")));
+                }
+
+                let mut hasher = DefaultHasher::new();
+                intent.input_paths.hash(&mut hasher);
+                output_path.hash(&mut hasher);
+                let list_path = std::env::temp_dir().join(format!("ffrs-concat-{:016x}.txt", hasher.finish()));
+
+                cmd.args(["-f", "concat", "-safe", "0", "-i"])
+                    .arg(&list_path)
+                    .explain(6, format!("read the {} clips in order from a list file", count), intent.source("input"))
+                    .args(["-c", "copy"])
+                    .explain(2, "copy the streams without re-encoding, since the clips share their codecs", None)
+                    .output(output_path)
+                    .explain(1, "write the joined clip to this file", intent.source("output"))
+                    .support_file(list_path, list);
+            },
+            ConcatMethod::Filter { video, audio } => {
+                let extension = output_path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+                let audio_only = containers::lookup_container(&extension).is_some_and(|container| container.video.is_empty());
+                let video = video && !audio_only;
+                let streams = match (video, audio) {
+                    (true, true) => "video and audio",
+                    (true, false) => "video",
+                    (false, true) => "audio",
+                    (false, false) if audio_only => {
+                        return Err(format!("The clips have no audio to join into a .{} file", extension).into());
+                    },
+                    (false, false) => return Err("The clips have no video or audio to join".into()),
+                };
+
+                for (index, input) in intent.input_paths.iter().enumerate() {
+                    cmd.arg("-i").arg(input).explain(2, format!("read clip {} of {}", index + 1, count), None);
+                }
+                cmd.args(concat_filter(count, video, audio).complex_args()).explain(
+                    2,
+                    format!("join the {} of the {} clips end to end, re-encoding them", streams, count),
+                    intent.source("operation"),
+                );
+                if video {
+                    cmd.args(["-map", "[v]"]).explain(2, "use the joined video", None);
+                }
+                if audio {
+                    cmd.args(["-map", "[a]"]).explain(2, "use the joined audio", None);
+                }
+                let output_reason = match intent.source("output") {
+                    Some(_) => "write the joined clip to this file",
+                    None => "write the joined clip to this file, named after the first clip",
+                };
+                cmd.output(output_path).explain(1, output_reason, intent.source("output"));
+            },
+        }
+
+        Ok(cmd)
+    }
+
+    /// Adds the options for a chain of steps run in a single pass.
+    ///
+    /// A trim becomes seek options, every resize and the frame filter of a
+    /// final frame grab join one `-vf` filterchain, and a transcode sets the
+    /// encoders, so nothing is written between the steps. A step that makes
+    /// the final product, such as a gif or the audio track, must come last.
+    /// Steps that cannot share a pass, like a resize before extracting the
+    /// audio, are an error.
+    fn pipeline_options(
+        &self,
+        intent: &Intent,
+        steps: &[Operation],
+        output_path: &std::path::Path,
+        input_options: &mut Options,
+        output_options: &mut Options,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut cut = None;
+        let mut resizes = Vec::new();
+        let mut codecs = None;
+        let mut product = None;
+
+        for step in steps {
+            if let Some(last) = product {
+                return Err(format!(
+                    "Cannot {} after '{}', which makes the final result",
+                    step_name(step),
+                    step_name(last)
+                ).into());
+            }
+
+            let repeated = match step {
+                Operation::Convert => false,
+                Operation::Resize(resolution) => {
+                    resizes.push(*resolution);
+                    false
+                },
+                Operation::Trim { range, mode } => cut.replace((*range, *mode)).is_some(),
+                Operation::Transcode { video, audio } => codecs.replace((*video, *audio)).is_some(),
+                Operation::Gif(_) | Operation::Thumbnail { .. } | Operation::ExtractFrames(_) | Operation::ExtractAudio(_) => {
+                    product = Some(step);
+                    false
+                },
+                Operation::Concat(_) | Operation::Inspect | Operation::Pipeline(_) => {
+                    return Err(format!("Cannot {} as one step of a chain", step_name(step)).into());
+                },
+            };
+            if repeated {
+                return Err(format!("Cannot {} twice in one chain; say it once", step_name(step)).into());
+            }
+        }
+
+        let conflict = match product {
+            Some(Operation::ExtractAudio(_) | Operation::Gif(_)) if !resizes.is_empty() => Some("resize"),
+            Some(Operation::ExtractAudio(_) | Operation::Gif(_) | Operation::Thumbnail { .. } | Operation::ExtractFrames(_))
+                if codecs.is_some() => Some("transcode"),
+            Some(Operation::Gif(GifOptions { range: Some(_), .. }) | Operation::Thumbnail { time: Some(_) })
+                if cut.is_some() => Some("trim"),
+            _ => None,
+        };
+        if let (Some(step), Some(product)) = (conflict, product) {
+            let hint = match product {
+                Operation::Gif(_) if step == "resize" => "; give the gif a width instead, as in 'make a gif 320 wide'",
+                Operation::Gif(_) | Operation::Thumbnail { .. } if step == "trim" => "; give the time in one place",
+                _ => "",
+            };
+            return Err(format!("Cannot combine '{}' and '{}' in one run{}", step, step_name(product), hint).into());
+        }
+
+        if let Some(Operation::Gif(options)) = product {
+            let range = cut.map(|(range, _)| range).or(options.range);
+            self.gif_options(intent, &GifOptions { range, ..*options }, input_options, output_options);
+            return Ok(());
+        }
+
+        if let Some((range, mode)) = cut {
+            self.trim_options(intent, &range, mode, false, input_options, output_options);
+        }
+
+        let mut filters: Vec<(Filter, String)> = resizes.iter()
+            .map(|resolution| (scale_filter(resolution), defaulted(describe_scale(resolution), intent.source("size"))))
+            .collect();
+        match product {
+            Some(Operation::Thumbnail { time: Some(time) }) => input_options.push(
+                ["-ss".to_string(), format_timestamp(time.seconds())],
+                format!("jump to {} before taking the frame", format_timestamp(time.seconds())),
+                intent.source("time"),
+            ),
+            Some(Operation::Thumbnail { time: None }) => {
+                filters.push((Filter::new("thumbnail"), "let ffmpeg pick a representative frame".to_string()));
+            },
+            Some(Operation::ExtractFrames(fps)) => {
+                filters.push((
+                    Filter::new("fps").arg(fps.filter_value()),
+                    defaulted(format!("save {}", fps), intent.source("fps")),
+                ));
+            },
+            _ => {},
+        }
+
+        if !filters.is_empty() {
+            let (filters, reasons): (Vec<_>, Vec<_>) = filters.into_iter().unzip();
+            let chain = filters.into_iter().fold(Chain::new(), Chain::filter);
+            let source = [intent.source("size"), intent.source("fps")].into_iter().flatten().collect::<Vec<_>>().join(", ");
+            output_options.push(
+                Filtergraph::from(chain).video_args(),
+                reasons.join("; then "),
+                Some(source.as_str()).filter(|source| !source.is_empty()),
+            );
+        }
+
+        if let Some((video, audio)) = codecs {
+            self.transcode_options(intent, video, audio, output_path, output_options)?;
+        }
+
+        match product {
+            Some(Operation::Thumbnail { .. }) => {
+                output_options.push(["-frames:v", "1"], "save a single frame", intent.source("operation"));
+            },
+            Some(Operation::ExtractAudio(quality)) => {
+                self.audio_options(intent, *quality, output_path, output_options)?;
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    /// Adds the encoder options for a transcode.
+    ///
+    /// The codecs must fit the output container. A codec that is not given is
+    /// the container's preferred one, or H.264 video and AAC audio for a
+    /// container without a table entry; an audio-only container drops the
+    /// video instead.
+    fn transcode_options(
+        &self,
+        intent: &Intent,
+        video: Option<Codec>,
+        audio: Option<Codec>,
+        output_path: &std::path::Path,
+        output_options: &mut Options,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let extension = output_path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+        containers::check_codecs(&extension, video, audio)?;
+
+        let container = containers::lookup_container(&extension);
+        let fallback = |kind: CodecKind, name: &str| match container {
+            Some(container) => container.preferred(kind),
+            None => codecs::lookup_codec(name),
+        };
+        let default_reason = |encoder: &str, stream: &str| match container {
+            Some(container) => format!("encode the {} with {}, the default for .{}", stream, encoder, container.name()),
+            None => format!("encode the {} with {} by default", stream, encoder),
+        };
+
+        match video.or_else(|| fallback(CodecKind::Video, "h264")) {
+            Some(codec) if video.is_some() => output_options.push(
+                ["-c:v", codec.encoder],
+                format!("encode the video with {}", codec.encoder),
+                intent.source("vcodec"),
+            ),
+            Some(codec) => output_options.push(["-c:v", codec.encoder], default_reason(codec.encoder, "video"), None),
+            None => output_options.push(
+                ["-vn"],
+                format!("leave out the video, since .{} files hold only audio", extension),
+                None,
+            ),
+        }
+        match audio.or_else(|| fallback(CodecKind::Audio, "aac")) {
+            Some(codec) if audio.is_some() => output_options.push(
+                ["-c:a", codec.encoder],
+                format!("encode the audio with {}", codec.encoder),
+                intent.source("acodec"),
+            ),
+            Some(codec) => output_options.push(["-c:a", codec.encoder], default_reason(codec.encoder, "audio"), None),
+            None => {},
+        }
+
+        Ok(())
+    }
+
+    /// Adds the options for extracting the audio track.
+    ///
+    /// The encoder and quality follow the output extension: VBR for MP3 and
+    /// Vorbis, a bitrate for AAC and Opus, and lossless codecs for WAV and FLAC,
+    /// which ignore bitrates. `Quality::Copy` copies the audio stream as-is
+    /// when the output can hold it; MKA is also copied since it holds any codec.
+    fn audio_options(
+        &self,
+        intent: &Intent,
+        quality: Quality,
+        output_path: &std::path::Path,
+        output_options: &mut Options,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let extension = output_path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+
+        output_options.push(["-vn"], "leave out the video", intent.source("operation"));
+
+        match quality {
+            Quality::Copy { source } => {
+                check_audio_copy(intent, source, &extension)?;
+                output_options.push(["-c:a", "copy"], "copy the audio as it is, without re-encoding", intent.source("copy"));
+                return Ok(());
+            },
+            Quality::Bitrate(_) if extension == "mka" => {
+                return Err("A bitrate cannot be set when copying audio without re-encoding".into());
+            },
+            Quality::Default if extension == "mka" => {
+                output_options.push(
+                    ["-c:a", "copy"],
+                    "copy the audio as it is, since .mka files hold any codec",
+                    intent.source("output"),
+                );
+                return Ok(());
+            },
+            _ => {},
+        }
+
+        let (encoder, default_quality) = audio_encoding(&extension)
+            .ok_or_else(|| format!("Cannot extract audio into a .{} file", extension))?;
+
+        output_options.push(
+            ["-c:a", encoder],
+            format!("encode the audio with {}, the codec for .{} files", encoder, extension),
+            intent.source("output"),
+        );
+        match (quality, default_quality) {
+            (Quality::Bitrate(_), None) => {
+                return Err(format!("A bitrate does not apply to lossless .{} audio", extension).into());
+            },
+            (Quality::Bitrate(bitrate), Some(_)) => {
+                output_options.push(
+                    ["-b:a".to_string(), bitrate.ffmpeg_value()],
+                    format!("an audio bitrate of {}", bitrate),
+                    intent.source("bitrate"),
+                );
+            },
+            (_, Some((flag @ "-q:a", value))) => {
+                output_options.push(
+                    [flag, value],
+                    format!("variable bitrate at quality {}, a good default for .{}", value, extension),
+                    None,
+                );
+            },
+            (_, Some((flag, value))) => {
+                output_options.push(
+                    [flag, value],
+                    format!("an audio bitrate of {}bit/s, a good default for .{}", value, extension),
+                    None,
+                );
+            },
+            (_, None) => {},
+        }
+
+        Ok(())
+    }
+
+    /// Adds the options for a good-looking, compact animated GIF.
+    ///
+    /// A single filtergraph lowers the frame rate, scales with lanczos, builds
+    /// a palette from the clip itself with `palettegen` and maps the frames
+    /// onto it with `paletteuse`, instead of the generic 256-colour palette.
+    fn gif_options(&self, intent: &Intent, options: &GifOptions, input_options: &mut Options, output_options: &mut Options) {
+        match options.range.map(|range| range.span()) {
+            Some(TimeSpan::Between { start, end }) => {
+                if let Some(start) = start {
+                    input_options.push(
+                        ["-ss".to_string(), format_timestamp(start)],
+                        format!("start at {}", format_timestamp(start)),
+                        intent.source("start"),
+                    );
+                }
+                if let Some(end) = end {
+                    input_options.push(
+                        ["-to".to_string(), format_timestamp(end)],
+                        format!("stop at {}", format_timestamp(end)),
+                        intent.source("end"),
+                    );
+                }
+            },
+            Some(TimeSpan::For { start, duration }) => {
+                if let Some(start) = start {
+                    input_options.push(
+                        ["-ss".to_string(), format_timestamp(start)],
+                        format!("start at {}", format_timestamp(start)),
+                        intent.source("start"),
+                    );
+                }
+                input_options.push(
+                    ["-t".to_string(), format_timestamp(duration)],
+                    format!("take {} of video", format_timestamp(duration)),
+                    intent.source("duration"),
+                );
+            },
+            Some(TimeSpan::Last(duration)) => {
+                input_options.push(
+                    ["-sseof".to_string(), format!("-{}", format_timestamp(duration))],
+                    format!("take the last {} of video", format_timestamp(duration)),
+                    intent.source("from_end"),
+                );
+            },
+            None => {},
+        }
+
+        let source = [intent.source("fps"), intent.source("width")].into_iter().flatten().collect::<Vec<_>>().join(", ");
+
+        output_options.push(
+            gif_filter(&options.fps, options.width).video_args(),
+            format!(
+                "{} frames per second, {} pixels wide, with a colour palette made for this clip",
+                options.fps.filter_value(),
+                options.width
+            ),
+            Some(source.as_str()).filter(|source| !source.is_empty()),
+        );
+        output_options.push(["-loop", "0"], "loop forever", intent.source("operation"));
+    }
+
+    /// Adds the seek and length options for a trim.
+    ///
+    /// In `Copy` mode the seek happens on the input and streams are copied,
+    /// which is fast but snaps to keyframes. In `Accurate` mode the seek
+    /// happens on the output and the clip is re-encoded frame-exactly. When
+    /// `copy_streams` is false, other steps re-encode the clip anyway, so
+    /// nothing is copied whatever the mode.
+    fn trim_options(
+        &self,
+        intent: &Intent,
+        range: &TimeRange,
+        mode: TrimMode,
+        copy_streams: bool,
+        input_options: &mut Options,
+        output_options: &mut Options,
+    ) {
+        let accurate = mode == TrimMode::Accurate;
+        let copies = copy_streams && !accurate;
+        let how = if copies { ", snapping to the nearest keyframe" } else { "" };
+
+        let seek_options = if accurate { &mut *output_options } else { &mut *input_options };
+        if let Some(start) = range.start() {
+            seek_options.push(
+                ["-ss".to_string(), format_timestamp(start)],
+                format!("start at {}{}", format_timestamp(start), how),
+                intent.source("start"),
+            );
+        }
+        if let TimeSpan::Between { end: Some(end), .. } = range.span() {
+            seek_options.push(
+                ["-to".to_string(), format_timestamp(end)],
+                format!("stop at {}{}", format_timestamp(end), how),
+                intent.source("end"),
+            );
+        }
+        if let TimeSpan::Last(from_end) = range.span() {
+            input_options.push(
+                ["-sseof".to_string(), format!("-{}", format_timestamp(from_end))],
+                format!("start {} before the end", format_timestamp(from_end)),
+                intent.source("from_end"),
+            );
+        }
+        if let TimeSpan::For { duration, .. } = range.span() {
+            output_options.push(
+                ["-t".to_string(), format_timestamp(duration)],
+                format!("keep {}", format_timestamp(duration)),
+                intent.source("duration"),
+            );
+        }
+
+        if copies {
+            output_options.push(
+                ["-c", "copy"],
+                match intent.source("mode") {
+                    Some(_) => "copy the streams without re-encoding",
+                    None => "copy the streams without re-encoding, the fast default",
+                },
+                intent.source("mode"),
+            );
+        }
+    }
+}
+
+/// Arguments for one side of `-i`, in runs that each carry the reason they are there.
+#[derive(Debug, Default)]
+struct Options {
+    runs: Vec<(Vec<String>, String, Option<String>)>,
+}
+
+impl Options {
+    /// Adds a run of arguments with the reason for it and the words that asked for it.
+    fn push<I, S>(&mut self, args: I, reason: impl Into<String>, source: Option<&str>)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let args = args.into_iter().map(Into::into).collect();
+        self.runs.push((args, reason.into(), source.map(str::to_string)));
+    }
+
+    /// Appends every run to the command along with its explanation.
+    fn append_to(self, cmd: &mut FfmpegCommand) {
+        for (args, reason, source) in self.runs {
+            let count = args.len();
+            cmd.args(args).explain(count, reason, source.as_deref());
+        }
+    }
+}
+
+/// Returns the `scale` filter for a resize target.
+fn scale_filter(resolution: &Resolution) -> Filter {
+    let (width, height) = resolution.scale_arguments();
+    Filter::new("scale").arg(width).arg(height)
+}
+
+/// Returns the GIF filtergraph: the frame rate, a lanczos scale to `width`
+/// and a palette generated from the clip itself.
+pub(crate) fn gif_filter(fps: &FrameRate, width: Pixels) -> Filtergraph {
+    Filtergraph::new()
+        .chain(
+            Chain::new()
+                .filter(Filter::new("fps").arg(fps.filter_value()))
+                .filter(Filter::new("scale").arg(width).arg(-1).option("flags", "lanczos"))
+                .filter(Filter::new("split"))
+                .output("s0")
+                .output("s1"),
+        )
+        .chain(Chain::new().input("s0").filter(Filter::new("palettegen")).output("p"))
+        .chain(Chain::new().input("s1").input("p").filter(Filter::new("paletteuse")))
+}
+
+/// Returns the concat filtergraph joining `count` inputs into `[v]` with
+/// video and `[a]` with audio.
+pub(crate) fn concat_filter(count: usize, with_video: bool, with_audio: bool) -> Filtergraph {
+    let mut chain = Chain::new();
+    for index in 0..count {
+        if with_video {
+            chain = chain.input(format!("{}:v", index));
+        }
+        if with_audio {
+            chain = chain.input(format!("{}:a", index));
+        }
+    }
+
+    chain = chain.filter(
+        Filter::new("concat")
+            .option("n", count)
+            .option("v", u8::from(with_video))
+            .option("a", u8::from(with_audio)),
+    );
+    if with_video {
+        chain = chain.output("v");
+    }
+    if with_audio {
+        chain = chain.output("a");
+    }
+    Filtergraph::from(chain)
+}
+
+/// Returns the encoder an extracted audio track gets for an output extension,
+/// with the quality option used when no bitrate is requested.
+///
+/// Lossless formats have no quality option.
+pub(crate) fn audio_encoding(extension: &str) -> Option<(&'static str, Option<(&'static str, &'static str)>)> {
+    match extension {
+        "mp3" => Some(("libmp3lame", Some(("-q:a", "2")))),
+        "ogg" | "oga" => Some(("libvorbis", Some(("-q:a", "5")))),
+        "m4a" | "aac" | "mp4" => Some(("aac", Some(("-b:a", "192k")))),
+        "opus" => Some(("libopus", Some(("-b:a", "128k")))),
+        "wav" => Some(("pcm_s16le", None)),
+        "flac" => Some(("flac", None)),
+        _ => None,
+    }
+}
+
+/// Checks that copied audio fits the output format.
+///
+/// A codec known from inspecting the input must be one the format holds.
+/// When the codec is unknown, copying is refused into formats that hold a
+/// single codec, since ffmpeg fails unless the audio already uses it; other
+/// formats are left for ffmpeg to check.
+fn check_audio_copy(intent: &Intent, source: Option<Codec>, extension: &str) -> Result<(), String> {
+    let container = match containers::lookup_container(extension) {
+        Some(container) if extension != "mka" => container,
+        _ => return Ok(()),
+    };
+    let input = intent.input_path().map_or_else(|| "the input".to_string(), |path| path.display().to_string());
+
+    match source {
+        Some(codec) if container.holds(codec) => Ok(()),
+        Some(codec) => Err(format!(
+            "The audio of {} is {}, which a .{} file cannot hold without re-encoding. \
+             Save as .mka, which holds any codec, or leave out 'without re-encoding'",
+            input, codec.name, extension
+        )),
+        None if container.audio.len() == 1 => Err(format!(
+            "A .{} file only holds {} audio, and the audio codec of {} is not known, so it cannot be copied safely. \
+             Save as .mka, which holds any codec, or leave out 'without re-encoding'",
+            extension, container.audio[0], input
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Names a step the way a request would ask for it, for error messages.
+fn step_name(operation: &Operation) -> &'static str {
+    match operation {
+        Operation::Convert => "convert",
+        Operation::Resize(_) => "resize",
+        Operation::Transcode { .. } => "transcode",
+        Operation::ExtractAudio(_) => "extract the audio",
+        Operation::Trim { .. } => "trim",
+        Operation::Concat(_) => "join",
+        Operation::Gif(_) => "make a gif",
+        Operation::Thumbnail { .. } => "grab a frame",
+        Operation::ExtractFrames(_) => "extract frames",
+        Operation::Inspect => "inspect",
+        Operation::Pipeline(_) => "run a chain",
+    }
+}
+
+/// Marks a reason as a default when no words of the request asked for it.
+fn defaulted(reason: String, source: Option<&str>) -> String {
+    match source {
+        Some(_) => reason,
+        None => format!("{} by default", reason),
+    }
+}
+
+/// Describes a resize target in words, e.g. "resize to 1280 wide, keep the aspect ratio".
+fn describe_scale(resolution: &Resolution) -> String {
+    let keep_aspect = |size: String, side: &str| {
+        format!("resize to {}, keep the aspect ratio with an even {}", size, side)
+    };
+
+    match resolution {
+        Resolution::Exact { .. } => format!("resize to {}", resolution),
+        Resolution::Width(_) => keep_aspect(resolution.to_string(), "height"),
+        Resolution::Height(_) => keep_aspect(resolution.to_string(), "width"),
+        Resolution::Half => keep_aspect("half the original width".to_string(), "height"),
+        Resolution::Quarter => keep_aspect("a quarter of the original width".to_string(), "height"),
+        Resolution::Double => keep_aspect("twice the original width".to_string(), "height"),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::intent::types::{Intent, OperationType};
+    use crate::intent::params::{Bitrate, TimePoint};
+    use crate::intent::types::{Intent, Operation};
     use std::path::PathBuf;
 
     #[test]
     fn test_build_convert_command() {
         let builder = CommandBuilder::new();
         let intent = Intent {
-            operation: OperationType::Convert,
-            input_path: PathBuf::from("input.mp4"),
+            operation: Operation::Convert,
+            input_paths: vec![PathBuf::from("input.mp4")],
             output_path: PathBuf::from("output.avi"),
-            parameters: std::collections::HashMap::new(),
+            sources: std::collections::HashMap::new(),
         };
 
         let cmd = builder.build_command(&intent).unwrap();
-        assert_eq!(cmd, "ffmpeg -i \"input.mp4\" \"output.avi\"");
+        assert_eq!(cmd.to_string(), "ffmpeg -i input.mp4 output.avi");
     }
 
     #[test]
     fn test_build_resize_command() {
         let builder = CommandBuilder::new();
-        let mut params = std::collections::HashMap::new();
-        params.insert("width".to_string(), "1280".to_string());
-        params.insert("height".to_string(), "720".to_string());
-        
         let intent = Intent {
-            operation: OperationType::Resize,
-            input_path: PathBuf::from("input.mp4"),
+            operation: Operation::Resize(Resolution::exact(1280, 720).unwrap()),
+            input_paths: vec![PathBuf::from("input.mp4")],
             output_path: PathBuf::from("output.mp4"),
-            parameters: params,
+            sources: std::collections::HashMap::new(),
         };
 
         let cmd = builder.build_command(&intent).unwrap();
-        assert_eq!(cmd, "ffmpeg -i \"input.mp4\" -vf scale=1280:720 \"output.mp4\"");
+        assert_eq!(cmd.to_string(), "ffmpeg -i input.mp4 -vf scale=1280:720 output.mp4");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_build_transcode_checks_the_container() {
+        let builder = CommandBuilder::new();
+        let mut intent = Intent {
+            operation: Operation::Transcode { video: codecs::lookup_codec("h264"), audio: codecs::lookup_codec("aac") },
+            input_paths: vec![PathBuf::from("a.mp4")],
+            output_path: PathBuf::from("b.webm"),
+            sources: std::collections::HashMap::new(),
+        };
+        let error = builder.build_command(&intent).unwrap_err().to_string();
+        assert!(error.contains("webm needs vp9/vp8/av1 + opus/vorbis. Try 'using vp9 and opus'"), "{}", error);
+
+        intent.operation = Operation::Transcode { video: None, audio: codecs::lookup_codec("vorbis") };
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i a.mp4 -c:v libvpx-vp9 -c:a libvorbis b.webm");
+
+        intent.operation = Operation::Transcode { video: None, audio: None };
+        intent.output_path = PathBuf::from("b.mp3");
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i a.mp4 -vn -c:a libmp3lame b.mp3");
+
+        intent.output_path = PathBuf::from("b.wav");
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i a.mp4 -vn -c:a pcm_s16le b.wav");
+
+        intent.operation = Operation::Transcode { video: codecs::lookup_codec("h264"), audio: None };
+        let error = builder.build_command(&intent).unwrap_err().to_string();
+        assert!(error.contains("A .wav file cannot hold video (h264); it holds audio only"), "{}", error);
+    }
+
+    #[test]
+    fn test_build_explains_each_argument() {
+        let mut intent = Intent {
+            operation: Operation::Resize(Resolution::width(1280).unwrap()),
+            input_paths: vec![PathBuf::from("clip.mp4")],
+            output_path: PathBuf::from("clip_resized.mp4"),
+            sources: std::collections::HashMap::new(),
+        };
+        intent.sources.insert("input".to_string(), "clip.mp4".to_string());
+        intent.sources.insert("size".to_string(), "to 1280 wide".to_string());
+
+        let cmd = CommandBuilder::new().build_command(&intent).unwrap();
+        let explained: Vec<_> = cmd
+            .get_explained_args()
+            .into_iter()
+            .map(|(args, explanation)| {
+                let explanation = explanation.unwrap();
+                (args.len(), explanation.reason.clone(), explanation.source.clone())
+            })
+            .collect();
+
+        assert_eq!(
+            explained,
+            vec![
+                (2, "read the input file".to_string(), Some("clip.mp4".to_string())),
+                (
+                    2,
+                    "resize to 1280 wide, keep the aspect ratio with an even height".to_string(),
+                    Some("to 1280 wide".to_string())
+                ),
+                (1, "write the result to this file, named after the input".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_command_keeps_paths_with_spaces_intact() {
+        let builder = CommandBuilder::new();
+        let intent = Intent {
+            operation: Operation::Convert,
+            input_paths: vec![PathBuf::from("My Holiday.mp4")],
+            output_path: PathBuf::from("My Holiday.webm"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        let args: Vec<_> = cmd.get_args().iter().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(args, vec!["-i", "My Holiday.mp4", "My Holiday.webm"]);
+        assert_eq!(cmd.to_string(), "ffmpeg -i 'My Holiday.mp4' 'My Holiday.webm'");
+    }
+
+    #[test]
+    fn test_build_trim_command() {
+        let builder = CommandBuilder::new();
+        let range = TimeRange::between(Some(90.0), Some(165.0)).unwrap();
+        let mut intent = Intent {
+            operation: Operation::Trim { range, mode: TrimMode::Copy },
+            input_paths: vec![PathBuf::from("talk.mp4")],
+            output_path: PathBuf::from("excerpt.mp4"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -ss 00:01:30 -to 00:02:45 -i talk.mp4 -c copy excerpt.mp4");
+
+        intent.operation = Operation::Trim { range, mode: TrimMode::Accurate };
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i talk.mp4 -ss 00:01:30 -to 00:02:45 excerpt.mp4");
+    }
+
+    #[test]
+    fn test_build_concat_filter_command() {
+        let builder = CommandBuilder::new();
+        let intent = Intent {
+            operation: Operation::Concat(ConcatMethod::default()),
+            input_paths: vec![PathBuf::from("part1.mp4"), PathBuf::from("part2.mp4")],
+            output_path: PathBuf::from("full.mp4"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(
+            cmd.to_string(),
+            "ffmpeg -i part1.mp4 -i part2.mp4 -filter_complex '[0:v][0:a][1:v][1:a]concat=n=2:v=1:a=1[v][a]' \
+             -map '[v]' -map '[a]' full.mp4"
+        );
+        assert_eq!(cmd.get_support_files().count(), 0);
+    }
+
+    #[test]
+    fn test_build_concat_filter_joins_audio_only() {
+        let builder = CommandBuilder::new();
+        let mut intent = Intent {
+            operation: Operation::Concat(ConcatMethod::default()),
+            input_paths: vec![PathBuf::from("a.mp3"), PathBuf::from("b.mp3")],
+            output_path: PathBuf::from("all.mp3"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let audio_only = "ffmpeg -i a.mp3 -i b.mp3 -filter_complex '[0:a][1:a]concat=n=2:v=0:a=1[a]' -map '[a]' all.mp3";
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), audio_only);
+
+        intent.operation = Operation::Concat(ConcatMethod::Filter { video: false, audio: true });
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), audio_only);
+
+        intent.output_path = PathBuf::from("all.mka");
+        let cmd = builder.build_command(&intent).unwrap();
+        assert!(cmd.to_string().contains("'[0:a][1:a]concat=n=2:v=0:a=1[a]' -map '[a]' all.mka"), "{}", cmd);
+
+        intent.output_path = PathBuf::from("all.mp3");
+        intent.operation = Operation::Concat(ConcatMethod::Filter { video: true, audio: false });
+        let error = builder.build_command(&intent).unwrap_err().to_string();
+        assert_eq!(error, "The clips have no audio to join into a .mp3 file");
+    }
+
+    #[test]
+    fn test_build_concat_demuxer_command() {
+        let builder = CommandBuilder::new();
+        let intent = Intent {
+            operation: Operation::Concat(ConcatMethod::Demuxer),
+            input_paths: vec![PathBuf::from("part1.mp4"), PathBuf::from("it's.mp4")],
+            output_path: PathBuf::from("full.mp4"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        let (list_path, contents) = cmd.get_support_files().next().unwrap();
+        let args: Vec<_> = cmd.get_args().iter().map(|a| a.to_string_lossy().into_owned()).collect();
+
+        assert_eq!(args[..5], ["-f", "concat", "-safe", "0", "-i"]);
+        assert_eq!(args[5], list_path.to_string_lossy());
+        assert_eq!(args[6..], ["-c", "copy", "full.mp4"]);
+        assert_eq!(contents.lines().count(), 2);
+        assert!(contents.starts_with("file '"));
+        assert!(contents.ends_with("it'\\''s.mp4'\n"));
+    }
+
+    #[test]
+    fn test_build_gif_command() {
+        let builder = CommandBuilder::new();
+        let intent = Intent {
+            operation: Operation::Gif(GifOptions {
+                range: Some(TimeRange::starting_for(Some(5.0), 3.0).unwrap()),
+                fps: FrameRate::per_second(15.0).unwrap(),
+                ..GifOptions::default()
+            }),
+            input_paths: vec![PathBuf::from("clip.mp4")],
+            output_path: PathBuf::from("clip.gif"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(
+            cmd.to_string(),
+            "ffmpeg -ss 00:00:05 -t 00:00:03 -i clip.mp4 -vf \
+             'fps=15,scale=480:-1:flags=lanczos,split[s0][s1];[s0]palettegen[p];[s1][p]paletteuse' \
+             -loop 0 clip.gif"
+        );
+    }
+
+    #[test]
+    fn test_build_convert_to_gif_uses_palette() {
+        let builder = CommandBuilder::new();
+        let intent = Intent {
+            operation: Operation::Convert,
+            input_paths: vec![PathBuf::from("clip.mp4")],
+            output_path: PathBuf::from("clip.gif"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        assert!(cmd.to_string().contains("palettegen"));
+    }
+
+    #[test]
+    fn test_build_frame_commands() {
+        let builder = CommandBuilder::new();
+        let mut intent = Intent {
+            operation: Operation::Thumbnail { time: None },
+            input_paths: vec![PathBuf::from("video.mp4")],
+            output_path: PathBuf::from("video_thumbnail.jpg"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i video.mp4 -vf thumbnail -frames:v 1 video_thumbnail.jpg");
+
+        intent.operation = Operation::Thumbnail { time: Some(TimePoint::new(83.0).unwrap()) };
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -ss 00:01:23 -i video.mp4 -frames:v 1 video_thumbnail.jpg");
+
+        intent.operation = Operation::ExtractFrames(FrameRate::every(10.0).unwrap());
+        intent.output_path = PathBuf::from("frames/video_%04d.jpg");
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i video.mp4 -vf fps=1/10 frames/video_%04d.jpg");
+        assert_eq!(cmd.get_output(), Some(std::path::Path::new("frames/video_%04d.jpg")));
+    }
+
+    #[test]
+    fn test_build_extract_audio_commands() {
+        let builder = CommandBuilder::new();
+        let mut intent = Intent {
+            operation: Operation::ExtractAudio(Quality::Default),
+            input_paths: vec![PathBuf::from("talk.mp4")],
+            output_path: PathBuf::from("talk.mp3"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i talk.mp4 -vn -c:a libmp3lame -q:a 2 talk.mp3");
+
+        intent.operation = Operation::ExtractAudio(Quality::Bitrate(Bitrate::from_kbps(192).unwrap()));
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i talk.mp4 -vn -c:a libmp3lame -b:a 192k talk.mp3");
+
+        intent.output_path = PathBuf::from("talk.flac");
+        assert!(builder.build_command(&intent).is_err());
+
+        intent.operation = Operation::ExtractAudio(Quality::Default);
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i talk.mp4 -vn -c:a flac talk.flac");
+
+        intent.output_path = PathBuf::from("talk.m4a");
+        intent.operation = Operation::ExtractAudio(Quality::Copy { source: codecs::lookup_codec("aac") });
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i talk.mp4 -vn -c:a copy talk.m4a");
+    }
+
+    #[test]
+    fn test_build_audio_copy_checks_the_source_codec() {
+        let builder = CommandBuilder::new();
+        let mut intent = Intent {
+            operation: Operation::ExtractAudio(Quality::Copy { source: codecs::lookup_codec("opus") }),
+            input_paths: vec![PathBuf::from("a.mkv")],
+            output_path: PathBuf::from("a.m4a"),
+            sources: std::collections::HashMap::new(),
+        };
+        let error = builder.build_command(&intent).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "The audio of a.mkv is opus, which a .m4a file cannot hold without re-encoding. \
+             Save as .mka, which holds any codec, or leave out 'without re-encoding'"
+        );
+
+        intent.output_path = PathBuf::from("a.opus");
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i a.mkv -vn -c:a copy a.opus");
+
+        intent.operation = Operation::ExtractAudio(Quality::Copy { source: None });
+        let error = builder.build_command(&intent).unwrap_err().to_string();
+        assert!(error.starts_with("A .opus file only holds opus audio, and the audio codec of a.mkv is not known"), "{}", error);
+
+        for output in ["a.mka", "b.mkv"] {
+            intent.output_path = PathBuf::from(output);
+            let cmd = builder.build_command(&intent).unwrap();
+            assert_eq!(cmd.to_string(), format!("ffmpeg -i a.mkv -vn -c:a copy {}", output));
+        }
+    }
+
+    #[test]
+    fn test_build_pipeline_in_one_pass() {
+        let builder = CommandBuilder::new();
+        let trim = Operation::Trim {
+            range: TimeRange::between(Some(10.0), Some(300.0)).unwrap(),
+            mode: TrimMode::Copy,
+        };
+        let mut intent = Intent {
+            operation: Operation::Pipeline(vec![trim.clone(), Operation::Resize(Resolution::height(720).unwrap())]),
+            input_paths: vec![PathBuf::from("interview.mp4")],
+            output_path: PathBuf::from("interview.webm"),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -ss 00:00:10 -to 00:05:00 -i interview.mp4 -vf scale=-2:720 interview.webm");
+
+        intent.operation = Operation::Pipeline(vec![
+            Operation::Resize(Resolution::Half),
+            Operation::Transcode { video: crate::utils::codecs::lookup_codec("h265"), audio: None },
+            Operation::ExtractFrames(FrameRate::per_second(2.0).unwrap()),
+        ]);
+        intent.output_path = PathBuf::from("interview_%04d.jpg");
+        assert!(builder.build_command(&intent).unwrap_err().to_string().contains("Cannot combine 'transcode'"));
+
+        intent.operation = Operation::Pipeline(vec![
+            Operation::Resize(Resolution::Half),
+            Operation::ExtractFrames(FrameRate::per_second(2.0).unwrap()),
+        ]);
+        let cmd = builder.build_command(&intent).unwrap();
+        assert_eq!(cmd.to_string(), "ffmpeg -i interview.mp4 -vf scale=iw/2:-2,fps=2 interview_%04d.jpg");
+
+        intent.operation = Operation::Pipeline(vec![trim, Operation::Gif(GifOptions::default())]);
+        intent.output_path = PathBuf::from("interview.gif");
+        let cmd = builder.build_command(&intent).unwrap();
+        assert!(cmd.to_string().starts_with("ffmpeg -ss 00:00:10 -to 00:05:00 -i interview.mp4 -vf 'fps=10,"));
+    }
+
+    #[test]
+    fn test_build_pipeline_rejects_steps_that_cannot_share_a_pass() {
+        let builder = CommandBuilder::new();
+        let mut intent = Intent {
+            operation: Operation::Pipeline(vec![
+                Operation::Resize(Resolution::height(360).unwrap()),
+                Operation::Gif(GifOptions::default()),
+            ]),
+            input_paths: vec![PathBuf::from("clip.mp4")],
+            output_path: PathBuf::from("clip.gif"),
+            sources: std::collections::HashMap::new(),
+        };
+        let error = builder.build_command(&intent).unwrap_err().to_string();
+        assert!(error.contains("give the gif a width instead"), "{}", error);
+
+        intent.operation = Operation::Pipeline(vec![
+            Operation::ExtractAudio(Quality::Default),
+            Operation::Resize(Resolution::height(360).unwrap()),
+        ]);
+        let error = builder.build_command(&intent).unwrap_err().to_string();
+        assert!(error.contains("Cannot resize after 'extract the audio'"), "{}", error);
+
+        intent.operation = Operation::Pipeline(vec![
+            Operation::Resize(Resolution::height(360).unwrap()),
+            Operation::Inspect,
+        ]);
+        assert!(builder.build_command(&intent).is_err());
+    }
+
+    #[test]
+    fn test_build_inspect_command() {
+        let builder = CommandBuilder::new();
+        let intent = Intent {
+            operation: Operation::Inspect,
+            input_paths: vec![PathBuf::from("video.mp4")],
+            output_path: PathBuf::new(),
+            sources: std::collections::HashMap::new(),
+        };
+
+        let cmd = builder.build_command(&intent).unwrap();
+        let print_format = if cfg!(feature = "serde") { "json" } else { "default" };
+        assert_eq!(cmd.to_string(), format!("ffprobe -v error -print_format {} -show_format -show_streams video.mp4", print_format));
+        assert_eq!(cmd.get_output(), None);
+    }
+
+    #[test]
+    fn test_build_refuses_to_overwrite_input() {
+        let builder = CommandBuilder::new();
+        let dir = tempfile::tempdir().unwrap();
+        let video = dir.path().join("video.mp4");
+        std::fs::write(&video, b"").unwrap();
+
+        let intent = Intent {
+            operation: Operation::Resize(Resolution::default()),
+            input_paths: vec![video.clone()],
+            output_path: dir.path().join("video_resized.mp4"),
+            sources: std::collections::HashMap::new(),
+        };
+        assert!(builder.build_command(&intent).is_ok());
+
+        let result = builder.build_command_with_output_path(&intent, dir.path().join(".").join("video.mp4"));
+        assert!(result.unwrap_err().to_string().contains("is the input file"));
+
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link.mp4");
+            std::os::unix::fs::symlink(&video, &link).unwrap();
+            assert!(builder.build_command_with_output_path(&intent, link).is_err());
+        }
+    }
+}
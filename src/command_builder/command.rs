@@ -0,0 +1,317 @@
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A fully structured ffmpeg invocation.
+///
+/// The program and each argument are kept as separate values so that paths
+/// containing spaces or quotes reach ffmpeg untouched. The printable form
+/// produced by `Display` is for humans only and is never re-parsed.
+///
+/// Some commands read helper files, such as the list file of the concat
+/// demuxer. Those are carried as support files that the runner writes before
+/// starting ffmpeg and removes afterwards, so building a command never
+/// writes to the filesystem. It only reads it, when the builder resolves
+/// paths to check that the output is not one of the inputs.
+///
+/// Runs of arguments can carry an `Explanation` of why they are there, which
+/// `explained` prints next to them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FfmpegCommand {
+    program: OsString,
+    args: Vec<OsString>,
+    output: Option<PathBuf>,
+    support_files: Vec<(PathBuf, String)>,
+    explanations: Vec<(Range<usize>, Explanation)>,
+}
+
+/// Why a run of arguments is part of a command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// What the arguments do, in plain words
+    pub reason: String,
+    /// The words of the request that asked for them, if any
+    pub source: Option<String>,
+}
+
+impl FfmpegCommand {
+    /// Creates a new command for the given program with no arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The executable to run, usually `ffmpeg`
+    pub fn new<S: Into<OsString>>(program: S) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            output: None,
+            support_files: Vec::new(),
+            explanations: Vec::new(),
+        }
+    }
+
+    /// Appends a single argument.
+    pub fn arg<S: Into<OsString>>(&mut self, arg: S) -> &mut Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Appends several arguments in order.
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Appends the output file argument and remembers it as the command's output.
+    pub fn output<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        let path = path.into();
+        self.args.push(path.clone().into_os_string());
+        self.output = Some(path);
+        self
+    }
+
+    /// Attaches a reason to the last `count` arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - How many of the most recently added arguments the reason covers
+    /// * `reason` - What the arguments do, in plain words
+    /// * `source` - The words of the request that asked for them, if any
+    pub fn explain<R: Into<String>>(&mut self, count: usize, reason: R, source: Option<&str>) -> &mut Self {
+        let end = self.args.len();
+        let explanation = Explanation {
+            reason: reason.into(),
+            source: source.map(str::to_string),
+        };
+        self.explanations.push((end.saturating_sub(count)..end, explanation));
+        self
+    }
+
+    /// Returns a copy of this command that writes to `path` instead of its current output.
+    ///
+    /// A command without an output gets `path` appended as one. The old
+    /// output's explanation moves to the new one.
+    pub fn with_output<P: Into<PathBuf>>(&self, path: P) -> Self {
+        let mut cmd = self.clone();
+        let mut moved = None;
+        if let Some(old) = cmd.output.take() {
+            if let Some(index) = cmd.args.iter().rposition(|arg| *arg == *old.as_os_str()) {
+                cmd.args.remove(index);
+                if let Some(position) = cmd.explanations.iter().position(|(range, _)| *range == (index..index + 1)) {
+                    moved = Some(cmd.explanations.remove(position).1);
+                }
+                cmd.shift_explanations(index, -1);
+            }
+        }
+        cmd.output(path);
+        if let Some(explanation) = moved {
+            let end = cmd.args.len();
+            cmd.explanations.push((end - 1..end, explanation));
+        }
+        cmd
+    }
+
+    /// Tells ffmpeg whether it may overwrite existing output files.
+    ///
+    /// Puts `-y` (overwrite) or `-n` (never overwrite) in front of the other
+    /// arguments, replacing an earlier choice, so ffmpeg never stops to ask.
+    pub fn set_overwrite(&mut self, overwrite: bool) -> &mut Self {
+        if self.args.first().is_some_and(|arg| arg == "-y" || arg == "-n") {
+            self.args.remove(0);
+            self.explanations.retain(|(range, _)| *range != (0..1));
+            self.shift_explanations(0, -1);
+        }
+
+        self.args.insert(0, OsString::from(if overwrite { "-y" } else { "-n" }));
+        self.shift_explanations(0, 1);
+        let reason = if overwrite { "replace the output file if it exists" } else { "never replace an existing file" };
+        self.explanations.insert(0, (0..1, Explanation { reason: reason.to_string(), source: None }));
+        self
+    }
+
+    /// Moves explanations of arguments at or after `index` by `delta` places.
+    fn shift_explanations(&mut self, index: usize, delta: isize) {
+        for (range, _) in &mut self.explanations {
+            if range.start >= index {
+                range.start = range.start.saturating_add_signed(delta);
+                range.end = range.end.saturating_add_signed(delta);
+            }
+        }
+    }
+
+    /// Registers a file that must exist with the given contents while the command runs.
+    pub fn support_file<P: Into<PathBuf>>(&mut self, path: P, contents: String) -> &mut Self {
+        self.support_files.push((path.into(), contents));
+        self
+    }
+
+    /// Returns the program that will be executed.
+    pub fn get_program(&self) -> &OsStr {
+        &self.program
+    }
+
+    /// Returns the arguments that will be passed to the program.
+    pub fn get_args(&self) -> &[OsString] {
+        &self.args
+    }
+
+    /// Returns the output file, if one was set with `output`.
+    pub fn get_output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// Returns the support files as `(path, contents)` pairs.
+    pub fn get_support_files(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.support_files.iter().map(|(path, contents)| (path.as_path(), contents.as_str()))
+    }
+
+    /// Returns the arguments in runs, each with its explanation if it has one.
+    ///
+    /// Arguments without an explanation come as runs of one.
+    pub fn get_explained_args(&self) -> Vec<(&[OsString], Option<&Explanation>)> {
+        let mut runs = Vec::new();
+        let mut index = 0;
+        while index < self.args.len() {
+            let explained = self.explanations.iter()
+                .filter(|(range, _)| range.start == index && range.end > index)
+                .max_by_key(|(range, _)| range.end);
+            match explained {
+                Some((range, explanation)) => {
+                    let end = range.end.min(self.args.len());
+                    runs.push((&self.args[index..end], Some(explanation)));
+                    index = end;
+                },
+                None => {
+                    runs.push((&self.args[index..index + 1], None));
+                    index += 1;
+                },
+            }
+        }
+        runs
+    }
+
+    /// Returns a printable form of the command followed by one line per run
+    /// of arguments with the reason for it and the words that asked for it.
+    pub fn explained(&self) -> Explained<'_> {
+        Explained(self)
+    }
+
+    /// Converts this command into a `std::process::Command` ready to spawn.
+    pub fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        command
+    }
+}
+
+impl fmt::Display for FfmpegCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", quote_arg(&self.program))?;
+        for arg in &self.args {
+            write!(f, " {}", quote_arg(arg))?;
+        }
+        Ok(())
+    }
+}
+
+/// The `--explain` form of a command, returned by `FfmpegCommand::explained`.
+#[derive(Debug)]
+pub struct Explained<'a>(&'a FfmpegCommand);
+
+impl fmt::Display for Explained<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let runs: Vec<_> = self.0.get_explained_args().into_iter()
+            .map(|(args, explanation)| {
+                let args = args.iter().map(|arg| quote_arg(arg)).collect::<Vec<_>>().join(" ");
+                (args, explanation)
+            })
+            .collect();
+        let width = runs.iter().map(|(args, _)| args.chars().count()).max().unwrap_or(0);
+
+        write!(f, "{}", self.0)?;
+        for (args, explanation) in runs {
+            let line = match explanation {
+                Some(Explanation { reason, source: Some(source) }) => {
+                    format!("  {:<width$}  {} (from \"{}\")", args, reason, source)
+                },
+                Some(Explanation { reason, source: None }) => format!("  {:<width$}  {}", args, reason),
+                None => format!("  {}", args),
+            };
+            write!(f, "\n{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes an argument for display using POSIX shell single-quote rules.
+///
+/// Arguments made only of characters that are safe in a shell are printed
+/// as-is so the common case stays readable.
+fn quote_arg(arg: &OsStr) -> String {
+    let arg = arg.to_string_lossy();
+    let is_safe = |c: char| c.is_alphanumeric() || "_-./:=,+@%^".contains(c);
+
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        arg.into_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_quotes_only_when_needed() {
+        let mut cmd = FfmpegCommand::new("ffmpeg");
+        cmd.args(["-i", "My Holiday.mp4", "it's.avi", "plain.mp4"]);
+
+        assert_eq!(cmd.to_string(), "ffmpeg -i 'My Holiday.mp4' 'it'\\''s.avi' plain.mp4");
+    }
+
+    #[test]
+    fn test_args_are_kept_verbatim() {
+        let mut cmd = FfmpegCommand::new("ffmpeg");
+        cmd.arg("-i").arg("My Holiday.mp4");
+
+        assert_eq!(cmd.get_program(), "ffmpeg");
+        assert_eq!(cmd.get_args(), &[OsString::from("-i"), OsString::from("My Holiday.mp4")]);
+    }
+
+    #[test]
+    fn test_with_output_and_overwrite_flag() {
+        let mut cmd = FfmpegCommand::new("ffmpeg");
+        cmd.arg("-i").arg("in.mp4").output("out.mp4");
+        cmd.set_overwrite(false).set_overwrite(true);
+
+        let moved = cmd.with_output("dir/.out.partial.mp4");
+        assert_eq!(moved.to_string(), "ffmpeg -y -i in.mp4 dir/.out.partial.mp4");
+        assert_eq!(moved.get_output(), Some(Path::new("dir/.out.partial.mp4")));
+    }
+
+    #[test]
+    fn test_explained_lists_runs_with_reasons_and_sources() {
+        let mut cmd = FfmpegCommand::new("ffmpeg");
+        cmd.arg("-i").arg("clip.mp4").explain(2, "read the input file", Some("clip.mp4"))
+            .args(["-vf", "scale=1280:-2"]).explain(2, "resize to 1280 wide", Some("to 1280 wide"))
+            .output("out.mp4")
+            .explain(1, "write the result here", None);
+        cmd.set_overwrite(false);
+
+        assert_eq!(
+            cmd.with_output(".out.partial.mp4").explained().to_string(),
+            "ffmpeg -n -i clip.mp4 -vf scale=1280:-2 .out.partial.mp4\n  \
+             -n                 never replace an existing file\n  \
+             -i clip.mp4        read the input file (from \"clip.mp4\")\n  \
+             -vf scale=1280:-2  resize to 1280 wide (from \"to 1280 wide\")\n  \
+             .out.partial.mp4   write the result here"
+        );
+    }
+}
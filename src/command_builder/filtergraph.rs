@@ -0,0 +1,252 @@
+use std::fmt;
+
+/// A single ffmpeg filter with its arguments, such as `scale=-2:720`.
+///
+/// Arguments are kept as given and escaped only when the filter is rendered,
+/// so a value holding `:`, `,`, `'` or `\` (a drawtext string or a Windows
+/// path) reaches ffmpeg as one value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    name: String,
+    args: Vec<(Option<String>, String)>,
+}
+
+impl Filter {
+    /// Creates a filter with no arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The ffmpeg filter name, e.g. `scale` or `fps`
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), args: Vec::new() }
+    }
+
+    /// Appends a positional argument, as in the `720` of `scale=-2:720`.
+    pub fn arg(mut self, value: impl ToString) -> Self {
+        self.args.push((None, value.to_string()));
+        self
+    }
+
+    /// Appends a named argument, as in the `flags=lanczos` of `scale`.
+    pub fn option(mut self, key: &str, value: impl ToString) -> Self {
+        self.args.push((Some(key.to_string()), value.to_string()));
+        self
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self.args.iter()
+            .map(|(key, value)| match key {
+                Some(key) => format!("{}={}", key, escape_value(value)),
+                None => escape_value(value),
+            })
+            .collect();
+
+        // The whole filter description is escaped again as part of the graph.
+        let description = match args.is_empty() {
+            true => self.name.clone(),
+            false => format!("{}={}", self.name, args.join(":")),
+        };
+        write!(f, "{}", escape_description(&description))
+    }
+}
+
+/// Filters applied one after another, with labeled pads at either end.
+///
+/// A chain without input labels reads the command's single input stream, and
+/// one without output labels writes the stream that gets encoded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chain {
+    inputs: Vec<String>,
+    filters: Vec<Filter>,
+    outputs: Vec<String>,
+}
+
+impl Chain {
+    /// Creates an empty chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an input pad, such as `0:v` for the first input's video or a
+    /// label written by another chain.
+    pub fn input(mut self, label: impl Into<String>) -> Self {
+        self.inputs.push(label.into());
+        self
+    }
+
+    /// Appends a filter to the end of the chain.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Adds an output pad that other chains or `-map` can refer to.
+    pub fn output(mut self, label: impl Into<String>) -> Self {
+        self.outputs.push(label.into());
+        self
+    }
+
+    /// Appends a filter in place, for chains built up step by step.
+    pub fn push(&mut self, filter: Filter) {
+        self.filters.push(filter);
+    }
+
+    /// Returns whether the chain has no filters.
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for label in &self.inputs {
+            write!(f, "[{}]", label)?;
+        }
+        let filters: Vec<String> = self.filters.iter().map(Filter::to_string).collect();
+        write!(f, "{}", filters.join(","))?;
+        for label in &self.outputs {
+            write!(f, "[{}]", label)?;
+        }
+        Ok(())
+    }
+}
+
+/// A complete ffmpeg filtergraph: one or more chains joined by their pads.
+///
+/// Renders as the value of `-vf`, `-af` or `-filter_complex`.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::command_builder::filtergraph::{Chain, Filter, Filtergraph};
+/// let graph = Filtergraph::from(
+///     Chain::new()
+///         .filter(Filter::new("scale").arg(-2).arg(720))
+///         .filter(Filter::new("drawtext").option("text", "Part 1: intro")),
+/// );
+/// assert_eq!(graph.video_args(), ["-vf", "scale=-2:720,drawtext=text=Part 1\\\\: intro"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filtergraph {
+    chains: Vec<Chain>,
+}
+
+impl Filtergraph {
+    /// Creates an empty filtergraph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a chain to the graph.
+    pub fn chain(mut self, chain: Chain) -> Self {
+        self.chains.push(chain);
+        self
+    }
+
+    /// Returns whether the graph has no filters at all.
+    pub fn is_empty(&self) -> bool {
+        self.chains.iter().all(Chain::is_empty)
+    }
+
+    /// Returns the graph as a video filter for a single input: `-vf <graph>`.
+    pub fn video_args(&self) -> [String; 2] {
+        ["-vf".to_string(), self.to_string()]
+    }
+
+    /// Returns the graph as an audio filter for a single input: `-af <graph>`.
+    pub fn audio_args(&self) -> [String; 2] {
+        ["-af".to_string(), self.to_string()]
+    }
+
+    /// Returns the graph as a complex filtergraph, which may read several
+    /// inputs and whose labeled outputs are chosen with `-map`.
+    pub fn complex_args(&self) -> [String; 2] {
+        ["-filter_complex".to_string(), self.to_string()]
+    }
+}
+
+impl From<Chain> for Filtergraph {
+    fn from(chain: Chain) -> Self {
+        Self { chains: vec![chain] }
+    }
+}
+
+impl fmt::Display for Filtergraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chains: Vec<String> = self.chains.iter().map(Chain::to_string).collect();
+        write!(f, "{}", chains.join(";"))
+    }
+}
+
+/// Escapes a single argument value, the first level of ffmpeg's filter escaping.
+///
+/// `:` would otherwise end the value, and `'` and `\` would start an escape.
+pub fn escape_value(value: &str) -> String {
+    escape(value, &['\\', '\'', ':'])
+}
+
+/// Escapes a filter description for use inside a graph, the second level of
+/// ffmpeg's filter escaping.
+///
+/// `,` and `;` would otherwise end the filter or chain, and `[` and `]` would
+/// start a pad label.
+fn escape_description(description: &str) -> String {
+    escape(description, &['\\', '\'', '[', ']', ',', ';'])
+}
+
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if special.contains(&ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_arguments_render_unchanged() {
+        let filter = Filter::new("scale").arg(480).arg(-1).option("flags", "lanczos");
+        assert_eq!(filter.to_string(), "scale=480:-1:flags=lanczos");
+        assert_eq!(Filter::new("thumbnail").to_string(), "thumbnail");
+        assert_eq!(Filter::new("fps").arg("1/10").to_string(), "fps=1/10");
+    }
+
+    #[test]
+    fn test_special_characters_are_escaped_twice() {
+        assert_eq!(escape_value("a:b"), "a\\:b");
+        assert_eq!(Filter::new("drawtext").option("text", "a:b").to_string(), "drawtext=text=a\\\\:b");
+        assert_eq!(Filter::new("drawtext").option("text", "it's").to_string(), "drawtext=text=it\\\\\\'s");
+        assert_eq!(Filter::new("drawtext").option("text", "1,2").to_string(), "drawtext=text=1\\,2");
+        assert_eq!(
+            Filter::new("subtitles").arg("C:\\subs\\a.srt").to_string(),
+            "subtitles=C\\\\:\\\\\\\\subs\\\\\\\\a.srt"
+        );
+    }
+
+    #[test]
+    fn test_chains_with_labeled_pads() {
+        let graph = Filtergraph::new()
+            .chain(
+                Chain::new()
+                    .filter(Filter::new("fps").arg(10))
+                    .filter(Filter::new("split"))
+                    .output("s0")
+                    .output("s1"),
+            )
+            .chain(Chain::new().input("s0").filter(Filter::new("palettegen")).output("p"))
+            .chain(Chain::new().input("s1").input("p").filter(Filter::new("paletteuse")));
+
+        assert_eq!(graph.to_string(), "fps=10,split[s0][s1];[s0]palettegen[p];[s1][p]paletteuse");
+        assert_eq!(graph.complex_args()[0], "-filter_complex");
+        assert_eq!(Filtergraph::from(Chain::new().filter(Filter::new("volume").arg(2))).audio_args(), ["-af", "volume=2"]);
+        assert!(Filtergraph::from(Chain::new().input("0:v")).is_empty());
+    }
+}
@@ -3,5 +3,9 @@
 //! This module converts structured intents into actual ffmpeg commands.
 
 pub mod builder;
+pub mod command;
+pub mod filtergraph;
+pub mod plan;
 
-pub use builder::*;
\ No newline at end of file
+pub use builder::*;
+pub use command::*;
@@ -0,0 +1,208 @@
+use crate::command_builder::builder::CommandBuilder;
+use crate::command_builder::command::FfmpegCommand;
+use crate::intent::types::Intent;
+
+/// A translated request ready to be reviewed and run later.
+///
+/// The intent is what gets run; `argv` and `display` show the command it
+/// builds, so a reviewer can read the plan without running ffrs. Editing
+/// either by hand does not change what runs: `verify` refuses a plan whose
+/// `argv` or `display` no longer matches its intent.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plan {
+    /// What to do, including the final output path
+    pub intent: Intent,
+    /// The program and its arguments, one per element
+    pub argv: Vec<String>,
+    /// The command as it would be typed into a shell
+    pub display: String,
+}
+
+/// Error types that can occur while reading or checking a plan.
+#[derive(Debug)]
+pub enum PlanError {
+    /// The plan file is not valid JSON or does not describe plans
+    Json(String),
+    /// The plan's intent cannot be built into a command
+    Build(String),
+    /// The plan's `argv` differs from the command its intent builds
+    ArgvMismatch { expected: String, found: String },
+    /// The plan's `display` differs from the command its intent builds
+    DisplayMismatch { expected: String, found: String },
+}
+
+impl std::fmt::Display for PlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanError::Json(msg) => write!(f, "Invalid plan: {}", msg),
+            PlanError::Build(msg) => write!(f, "Cannot build the plan's intent: {}", msg),
+            PlanError::ArgvMismatch { expected, found } => write!(
+                f,
+                "The plan's argv ({}) does not match the command its intent builds ({})",
+                found, expected
+            ),
+            PlanError::DisplayMismatch { expected, found } => write!(
+                f,
+                "The plan's display ({}) does not match the command its intent builds ({})",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+impl Plan {
+    /// Creates a plan from an intent and the command built from it.
+    ///
+    /// # Arguments
+    ///
+    /// * `intent` - The intent, whose output path should be the command's output
+    /// * `command` - The command the intent builds
+    pub fn new(intent: Intent, command: &FfmpegCommand) -> Self {
+        let argv = std::iter::once(command.get_program())
+            .chain(command.get_args().iter().map(|arg| arg.as_os_str()))
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+
+        Self { intent, argv, display: command.to_string() }
+    }
+
+    /// Builds the plan's command again and checks that it matches `argv` and `display`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the rebuilt command, or a `PlanError` if the
+    /// intent cannot be built or `argv` or `display` was changed after the
+    /// plan was made.
+    pub fn verify(&self) -> Result<FfmpegCommand, PlanError> {
+        let command = CommandBuilder::new()
+            .build_command(&self.intent)
+            .map_err(|e| PlanError::Build(e.to_string()))?;
+
+        let rebuilt = Plan::new(self.intent.clone(), &command);
+        if rebuilt.argv != self.argv {
+            return Err(PlanError::ArgvMismatch { expected: rebuilt.display, found: self.argv.join(" ") });
+        }
+        if rebuilt.display != self.display {
+            return Err(PlanError::DisplayMismatch { expected: rebuilt.display, found: self.display.clone() });
+        }
+        Ok(command)
+    }
+}
+
+/// Writes plans as pretty-printed JSON: a single object for one plan, an array otherwise.
+#[cfg(feature = "serde")]
+pub fn plans_to_json(plans: &[Plan]) -> String {
+    let json = match plans {
+        [plan] => serde_json::to_string_pretty(plan),
+        plans => serde_json::to_string_pretty(plans),
+    };
+    json.unwrap_or_default()
+}
+
+/// Reads plans from JSON holding either a single plan object or an array of them.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::{CommandBuilder, Parser, Tokenizer};
+/// use ffrs::command_builder::plan::{plans_from_json, plans_to_json, Plan};
+/// let intent = Parser::new(Tokenizer::new("convert a.mp4 to b.webm").tokenize()).parse().unwrap();
+/// let command = CommandBuilder::new().build_command(&intent).unwrap();
+/// let plans = plans_from_json(&plans_to_json(&[Plan::new(intent, &command)])).unwrap();
+/// assert_eq!(plans[0].argv, ["ffmpeg", "-i", "a.mp4", "b.webm"]);
+/// ```
+#[cfg(feature = "serde")]
+pub fn plans_from_json(json: &str) -> Result<Vec<Plan>, PlanError> {
+    let value: serde_json::Value = serde_json::from_str(json).map_err(|e| PlanError::Json(e.to_string()))?;
+    let plans = match value {
+        serde_json::Value::Array(_) => serde_json::from_value(value),
+        value => serde_json::from_value(value).map(|plan| vec![plan]),
+    };
+    plans.map_err(|e| PlanError::Json(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::{Parser, Tokenizer};
+
+    fn plan(command: &str) -> Plan {
+        let intent = Parser::new(Tokenizer::new(command).tokenize()).parse().unwrap();
+        let command = CommandBuilder::new().build_command(&intent).unwrap();
+        Plan::new(intent, &command)
+    }
+
+    #[test]
+    fn test_verify_rejects_edited_argv() {
+        let mut plan = plan("trim talk.mp4 from 1:30 to 2:45 as excerpt.mp4");
+        assert!(plan.verify().is_ok());
+
+        plan.argv.push("-an".to_string());
+        assert!(matches!(plan.verify(), Err(PlanError::ArgvMismatch { .. })));
+    }
+
+    #[test]
+    fn test_verify_rejects_edited_display() {
+        let mut plan = plan("trim talk.mp4 from 1:30 to 2:45 as excerpt.mp4");
+        plan.display = plan.display.replace("excerpt.mp4", "other.mp4");
+
+        let error = plan.verify().unwrap_err();
+        assert!(matches!(error, PlanError::DisplayMismatch { .. }));
+        assert!(error.to_string().starts_with("The plan's display (ffmpeg -ss 00:01:30"), "{}", error);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_round_trip() {
+        let plans = vec![
+            plan("resize clip.mp4 to 720p"),
+            plan("transcode a.mkv to b.mp4 using h265 and opus"),
+            plan("extract audio from talk.mp4 at 96 kbps"),
+            plan("make a gif from clip.mp4 from 0:05 for 3 seconds"),
+            plan("join a.mp4 and b.mp4 into c.mp4"),
+        ];
+
+        let json = plans_to_json(&plans);
+        assert_eq!(plans_from_json(&json).unwrap(), plans);
+
+        let single = plans_to_json(&plans[..1]);
+        assert!(single.starts_with('{'));
+        assert!(single.contains("\"resize\": {\n"));
+        assert_eq!(plans_from_json(&single).unwrap(), plans[..1]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_rejects_invalid_settings() {
+        let json = plans_to_json(&[plan("extract audio from talk.mp4 at 96 kbps")]);
+        let error = plans_from_json(&json.replace("96", "5000")).unwrap_err();
+        assert!(error.to_string().contains("5000 kbps is not a valid audio bitrate"));
+
+        let json = plans_to_json(&[plan("transcode a.mkv to b.mp4 using h265")]);
+        assert!(plans_from_json(&json.replace("\"h265\"", "\"divx\"")).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_rejects_invalid_params() {
+        let rejects = |command: &str, from: &str, to: &str, message: &str| {
+            let json = plans_to_json(&[plan(command)]);
+            assert!(json.contains(from), "{} is not in {}", from, json);
+            let error = plans_from_json(&json.replace(from, to)).unwrap_err();
+            assert!(error.to_string().contains(message), "{}: {}", command, error);
+        };
+
+        let gif = "make a gif from clip.mp4 from 0:05 to 0:10 at 15 fps 320 wide";
+        rejects(gif, "\"width\": 320", "\"width\": 0", "0 pixels is not a valid size");
+        rejects(gif, "\"end\": 10.0", "\"end\": 1.0", "end time 00:00:01 is not after start time 00:00:05");
+        rejects(gif, "\"per_second\": 15.0", "\"per_second\": -1.0", "-1 fps is not a valid frame rate");
+        rejects("resize clip.mp4 to 1280x720", "\"height\": 720", "\"height\": 0", "0 pixels is not a valid size");
+        rejects("trim talk.mp4 from 1:30 to 2:45", "\"start\": 90.0", "\"start\": -90.0", "-90 seconds is not a valid time");
+        rejects("trim talk.mp4 from 1:30 for 20 seconds", "\"duration\": 20.0", "\"duration\": 0.0", "a length of 0 seconds");
+        rejects("extract one frame every 10 seconds from talk.mp4", "\"every\": 10.0", "\"every\": 0.0", "every 0 seconds");
+        rejects("grab a frame from talk.mp4 at 1:23", "\"time\": 83.0", "\"time\": -5.0", "-5 seconds is not a valid time");
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+
+/// How a single job of a batch ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobStatus {
+    /// ffmpeg finished successfully
+    Succeeded,
+    /// The job could not be built or ffmpeg failed, with the reason
+    Failed(String),
+    /// The job was not run, with the reason
+    Skipped(String),
+}
+
+impl JobStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed(_) => "failed",
+            JobStatus::Skipped(_) => "skipped",
+        }
+    }
+
+    fn detail(&self) -> &str {
+        match self {
+            JobStatus::Succeeded => "",
+            JobStatus::Failed(reason) | JobStatus::Skipped(reason) => reason,
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobStatus::Succeeded => write!(f, "{}", self.label()),
+            _ => write!(f, "{} ({})", self.label(), self.detail()),
+        }
+    }
+}
+
+/// The outcome of every job in a batch, printed as a table once it is done.
+#[derive(Debug, Clone, Default)]
+pub struct BatchSummary {
+    jobs: Vec<(String, JobStatus)>,
+}
+
+impl BatchSummary {
+    /// Creates an empty summary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records how a job ended.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - A short description of the job, such as `in.mov -> out.mp4`
+    /// * `status` - How the job ended
+    pub fn record<S: Into<String>>(&mut self, job: S, status: JobStatus) {
+        self.jobs.push((job.into(), status));
+    }
+
+    /// Returns the number of jobs that succeeded.
+    pub fn succeeded(&self) -> usize {
+        self.count(|status| matches!(status, JobStatus::Succeeded))
+    }
+
+    /// Returns the number of jobs that failed.
+    pub fn failed(&self) -> usize {
+        self.count(|status| matches!(status, JobStatus::Failed(_)))
+    }
+
+    /// Returns the number of jobs that were skipped.
+    pub fn skipped(&self) -> usize {
+        self.count(|status| matches!(status, JobStatus::Skipped(_)))
+    }
+
+    /// Returns the process exit status for the whole batch.
+    ///
+    /// This is `0` when no job failed and `2`, the code for an ffmpeg
+    /// failure, otherwise. Skipped jobs do not count as failures.
+    pub fn exit_code(&self) -> i32 {
+        if self.failed() > 0 { 2 } else { 0 }
+    }
+
+    fn count(&self, predicate: impl Fn(&JobStatus) -> bool) -> usize {
+        self.jobs.iter().filter(|(_, status)| predicate(status)).count()
+    }
+}
+
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.jobs.iter()
+            .map(|(job, _)| job.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("Job".len());
+
+        writeln!(f, "{:<width$}  {:<9}  Detail", "Job", "Status")?;
+        for (job, status) in &self.jobs {
+            let line = format!("{:<width$}  {:<9}  {}", job, status.label(), status.detail());
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        write!(
+            f,
+            "{} succeeded, {} failed, {} skipped",
+            self.succeeded(),
+            self.failed(),
+            self.skipped()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_counts_and_table() {
+        let mut summary = BatchSummary::new();
+        summary.record("a.mov -> out/a.mp4", JobStatus::Succeeded);
+        summary.record("long name.mov -> out/long name.mp4", JobStatus::Failed("exit status 1".to_string()));
+        summary.record("c.mov -> out/c.mp4", JobStatus::Skipped("output exists".to_string()));
+
+        assert_eq!((summary.succeeded(), summary.failed(), summary.skipped()), (1, 1, 1));
+        assert_eq!(summary.exit_code(), 2);
+        assert_eq!(
+            summary.to_string(),
+            "Job                                 Status     Detail\n\
+             a.mov -> out/a.mp4                  succeeded\n\
+             long name.mov -> out/long name.mp4  failed     exit status 1\n\
+             c.mov -> out/c.mp4                  skipped    output exists\n\
+             1 succeeded, 1 failed, 1 skipped"
+        );
+    }
+}
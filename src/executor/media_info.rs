@@ -0,0 +1,460 @@
+use crate::command_builder::command::FfmpegCommand;
+use crate::executor::runner::ExecutionError;
+use crate::utils::file_utils::format_size;
+use crate::utils::time::format_timestamp;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The type of a stream inside a media file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    /// A video stream
+    Video,
+    /// An audio stream
+    Audio,
+    /// A subtitle stream
+    Subtitle,
+    /// Any other stream, such as data or attachments
+    Other,
+}
+
+/// Properties of one stream as reported by ffprobe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo {
+    /// The stream's index within the file
+    pub index: usize,
+    /// Whether the stream is video, audio, subtitle or other
+    pub kind: StreamKind,
+    /// The codec name (e.g., `h264`, `aac`)
+    pub codec: Option<String>,
+    /// The codec profile (e.g., `High`)
+    pub profile: Option<String>,
+    /// The frame width in pixels, for video
+    pub width: Option<u32>,
+    /// The frame height in pixels, for video
+    pub height: Option<u32>,
+    /// The pixel format (e.g., `yuv420p`), for video
+    pub pixel_format: Option<String>,
+    /// The average frame rate in frames per second, for video
+    pub frame_rate: Option<f64>,
+    /// The sample rate in hertz, for audio
+    pub sample_rate: Option<u32>,
+    /// The number of channels, for audio
+    pub channels: Option<u32>,
+    /// The stream's bitrate in bits per second
+    pub bit_rate: Option<u64>,
+    /// The language tag (e.g., `eng`)
+    pub language: Option<String>,
+}
+
+/// A summary of a media file's container and streams, read with ffprobe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaInfo {
+    /// The file that was inspected
+    pub path: PathBuf,
+    /// The container's short name (e.g., `matroska,webm`)
+    pub container: Option<String>,
+    /// The container's descriptive name (e.g., `Matroska / WebM`)
+    pub container_long_name: Option<String>,
+    /// The duration in seconds
+    pub duration: Option<f64>,
+    /// The overall bitrate in bits per second
+    pub bit_rate: Option<u64>,
+    /// The file size in bytes
+    pub size: Option<u64>,
+    /// The streams in the file, in index order
+    pub streams: Vec<StreamInfo>,
+}
+
+impl MediaInfo {
+    /// Builds the ffprobe command that reports a file's format and streams as JSON.
+    ///
+    /// Without the `serde` feature there is no JSON parser, so ffprobe's
+    /// default writer is asked for instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The media file to inspect
+    pub fn probe_command(path: &Path) -> FfmpegCommand {
+        let print_format = if cfg!(feature = "serde") { "json" } else { "default" };
+        let mut cmd = FfmpegCommand::new("ffprobe");
+        cmd.args(["-v", "error", "-print_format", print_format, "-show_format", "-show_streams"])
+            .arg(path);
+        cmd
+    }
+
+    /// Runs ffprobe on a file and reads its properties.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The media file to inspect
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `MediaInfo` or an `ExecutionError` if ffprobe
+    /// is missing, fails, or prints something unreadable.
+    pub fn probe(path: &Path) -> Result<Self, ExecutionError> {
+        let output = Self::probe_command(path)
+            .to_command()
+            .output()
+            .map_err(|e| ExecutionError::CommandFailed(format!("Failed to run ffprobe: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(ExecutionError::CommandFailed(format!(
+                "ffprobe could not read {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let output = String::from_utf8_lossy(&output.stdout);
+        #[cfg(feature = "serde")]
+        return Self::from_json(&output);
+        #[cfg(not(feature = "serde"))]
+        return Self::from_default_output(&output);
+    }
+
+    /// Reads the JSON printed by `ffprobe -print_format json -show_format -show_streams`.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The ffprobe output
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `MediaInfo` or an `ExecutionError` if the JSON is malformed.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, ExecutionError> {
+        use serde_json::Value;
+
+        /// Keeps an object's text and number fields, with its tags as `TAG:<name>`
+        /// like ffprobe's default writer prints them.
+        fn fields(object: &Value) -> Fields {
+            let mut fields = Fields::new();
+            for (key, value) in object.as_object().into_iter().flatten() {
+                match value {
+                    Value::String(text) => {
+                        fields.insert(key.clone(), text.clone());
+                    },
+                    Value::Number(number) => {
+                        fields.insert(key.clone(), number.to_string());
+                    },
+                    Value::Object(_) if key == "tags" => {
+                        fields.extend(fields_of_tags(value));
+                    },
+                    _ => {},
+                }
+            }
+            fields
+        }
+
+        fn fields_of_tags(tags: &Value) -> Fields {
+            fields(tags).into_iter().map(|(key, value)| (format!("TAG:{}", key), value)).collect()
+        }
+
+        let root: Value = serde_json::from_str(json)
+            .map_err(|e| ExecutionError::CommandFailed(format!("Unreadable ffprobe output: {}", e)))?;
+        let format = root.get("format")
+            .ok_or_else(|| ExecutionError::CommandFailed("Unreadable ffprobe output: no format".to_string()))?;
+        let streams = root["streams"].as_array().into_iter().flatten().map(fields).collect();
+
+        Ok(Self::from_fields(&fields(format), streams))
+    }
+
+    /// Reads the sections printed by `ffprobe -print_format default -show_format -show_streams`,
+    /// for builds without the `serde` feature.
+    ///
+    /// Fields of nested sections, such as a stream's side data, are skipped,
+    /// and so are lines that are not `key=value`, such as the later lines of
+    /// a tag whose value spans several.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `MediaInfo` or an `ExecutionError` if the
+    /// output has no format section.
+    #[cfg(not(feature = "serde"))]
+    pub fn from_default_output(output: &str) -> Result<Self, ExecutionError> {
+        let mut sections: Vec<(&str, Fields)> = Vec::new();
+        let mut streams = Vec::new();
+        let mut format = None;
+
+        for line in output.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix("[/").and_then(|rest| rest.strip_suffix(']')) {
+                match sections.pop() {
+                    Some(("STREAM", fields)) if name == "STREAM" && sections.is_empty() => streams.push(fields),
+                    Some(("FORMAT", fields)) if name == "FORMAT" && sections.is_empty() => format = Some(fields),
+                    _ => {},
+                }
+            } else if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                sections.push((name, Fields::new()));
+            } else if let (Some((key, value)), Some((_, fields))) = (line.split_once('='), sections.last_mut()) {
+                fields.entry(key.to_string()).or_insert_with(|| value.to_string());
+            }
+        }
+        let format = format
+            .ok_or_else(|| ExecutionError::CommandFailed("Unreadable ffprobe output: no format section".to_string()))?;
+
+        Ok(Self::from_fields(&format, streams))
+    }
+
+    /// Builds the summary from the fields of the format and of each stream.
+    fn from_fields(format: &Fields, streams: Vec<Fields>) -> Self {
+        Self {
+            path: PathBuf::from(field(format, "filename").unwrap_or_default()),
+            container: string_field(format, "format_name"),
+            container_long_name: string_field(format, "format_long_name"),
+            duration: number_field(format, "duration"),
+            bit_rate: number_field(format, "bit_rate").map(|rate| rate as u64),
+            size: number_field(format, "size").map(|size| size as u64),
+            streams: streams.iter().map(parse_stream).collect(),
+        }
+    }
+
+    /// Returns the streams of the given kind, in index order.
+    pub fn streams_of(&self, kind: StreamKind) -> impl Iterator<Item = &StreamInfo> {
+        self.streams.iter().filter(move |stream| stream.kind == kind)
+    }
+
+    /// Returns whether the file has at least one video stream.
+    pub fn has_video(&self) -> bool {
+        self.streams_of(StreamKind::Video).next().is_some()
+    }
+
+    /// Returns whether the file has at least one audio stream.
+    pub fn has_audio(&self) -> bool {
+        self.streams_of(StreamKind::Audio).next().is_some()
+    }
+}
+
+impl fmt::Display for MediaInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "File:      {}", self.path.display())?;
+        if let Some(container) = &self.container {
+            match &self.container_long_name {
+                Some(long_name) => writeln!(f, "Container: {} ({})", container, long_name)?,
+                None => writeln!(f, "Container: {}", container)?,
+            }
+        }
+        if let Some(duration) = self.duration {
+            writeln!(f, "Duration:  {}", format_timestamp(duration))?;
+        }
+        if let Some(size) = self.size {
+            writeln!(f, "Size:      {}", format_size(size))?;
+        }
+        if let Some(bit_rate) = self.bit_rate {
+            writeln!(f, "Bitrate:   {}", format_bit_rate(bit_rate))?;
+        }
+
+        write!(f, "Streams:")?;
+        for stream in &self.streams {
+            write!(f, "\n  #{} {}", stream.index, stream)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for StreamInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            StreamKind::Video => "video",
+            StreamKind::Audio => "audio",
+            StreamKind::Subtitle => "subtitle",
+            StreamKind::Other => "other",
+        };
+
+        let mut details = vec![self.codec.clone().unwrap_or_else(|| "unknown codec".to_string())];
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            details.push(format!("{}x{}", width, height));
+        }
+        if let Some(frame_rate) = self.frame_rate {
+            details.push(format!("{} fps", (frame_rate * 100.0).round() / 100.0));
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            details.push(format!("{} Hz", sample_rate));
+        }
+        if let Some(channels) = self.channels {
+            details.push(format!("{} channel{}", channels, if channels == 1 { "" } else { "s" }));
+        }
+        if let Some(bit_rate) = self.bit_rate {
+            details.push(format_bit_rate(bit_rate));
+        }
+        if let Some(language) = &self.language {
+            details.push(language.clone());
+        }
+
+        write!(f, "{}: {}", kind, details.join(", "))
+    }
+}
+
+/// The fields of one ffprobe section, with tags named `TAG:<name>`.
+type Fields = HashMap<String, String>;
+
+fn parse_stream(stream: &Fields) -> StreamInfo {
+    let kind = match field(stream, "codec_type") {
+        Some("video") => StreamKind::Video,
+        Some("audio") => StreamKind::Audio,
+        Some("subtitle") => StreamKind::Subtitle,
+        _ => StreamKind::Other,
+    };
+
+    StreamInfo {
+        index: field(stream, "index").and_then(|index| index.parse().ok()).unwrap_or_default(),
+        kind,
+        codec: string_field(stream, "codec_name"),
+        profile: string_field(stream, "profile"),
+        width: number_field(stream, "width").map(|width| width as u32),
+        height: number_field(stream, "height").map(|height| height as u32),
+        pixel_format: string_field(stream, "pix_fmt"),
+        frame_rate: field(stream, "avg_frame_rate").and_then(parse_rational),
+        sample_rate: number_field(stream, "sample_rate").map(|rate| rate as u32),
+        channels: number_field(stream, "channels").map(|channels| channels as u32),
+        bit_rate: number_field(stream, "bit_rate").map(|rate| rate as u64),
+        language: string_field(stream, "TAG:language"),
+    }
+}
+
+/// Returns a field's value, treating ffprobe's `N/A` and empty values as missing.
+fn field<'a>(fields: &'a Fields, key: &str) -> Option<&'a str> {
+    fields.get(key).map(String::as_str).filter(|value| !value.is_empty() && *value != "N/A")
+}
+
+fn string_field(fields: &Fields, key: &str) -> Option<String> {
+    field(fields, key).map(str::to_string)
+}
+
+fn number_field(fields: &Fields, key: &str) -> Option<f64> {
+    field(fields, key).and_then(|value| value.parse().ok())
+}
+
+/// Parses ffprobe's `num/den` rates, treating `0/0` as unknown.
+fn parse_rational(text: &str) -> Option<f64> {
+    let (numerator, denominator) = text.split_once('/')?;
+    let numerator: f64 = numerator.parse().ok()?;
+    let denominator: f64 = denominator.parse().ok()?;
+    (numerator > 0.0 && denominator > 0.0).then(|| numerator / denominator)
+}
+
+fn format_bit_rate(bits_per_second: u64) -> String {
+    if bits_per_second >= 1_000_000 {
+        format!("{:.1} Mb/s", bits_per_second as f64 / 1_000_000.0)
+    } else {
+        format!("{} kb/s", bits_per_second / 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    const SAMPLE: &str = r#"{
+        "streams": [
+            {
+                "index": 0, "codec_name": "h264", "profile": "High", "codec_type": "video",
+                "width": 1920, "height": 1080, "pix_fmt": "yuv420p",
+                "avg_frame_rate": "30000/1001", "bit_rate": "4500000",
+                "disposition": { "default": 1 }, "side_data_list": [{ "side_data_type": "Display Matrix" }],
+                "tags": { "title": "Line one\nline two" }
+            },
+            {
+                "index": 1, "codec_name": "aac", "codec_type": "audio",
+                "sample_rate": "48000", "channels": 2, "bit_rate": "128000",
+                "avg_frame_rate": "0/0", "tags": { "language": "eng" }
+            }
+        ],
+        "format": {
+            "filename": "video.mp4", "format_name": "mov,mp4,m4a,3gp,3g2,mj2",
+            "format_long_name": "QuickTime / MOV", "duration": "83.500000",
+            "size": "12897484", "bit_rate": "1235700"
+        }
+    }"#;
+
+    #[cfg(not(feature = "serde"))]
+    const SAMPLE: &str = "\
+[STREAM]
+index=0
+codec_name=h264
+profile=High
+codec_type=video
+width=1920
+height=1080
+pix_fmt=yuv420p
+avg_frame_rate=30000/1001
+bit_rate=4500000
+TAG:title=Line one
+line two
+[SIDE_DATA]
+side_data_type=Display Matrix
+[/SIDE_DATA]
+[/STREAM]
+[STREAM]
+index=1
+codec_name=aac
+profile=LC
+codec_type=audio
+sample_rate=48000
+channels=2
+avg_frame_rate=0/0
+bit_rate=128000
+TAG:language=eng
+[/STREAM]
+[FORMAT]
+filename=video.mp4
+format_name=mov,mp4,m4a,3gp,3g2,mj2
+format_long_name=QuickTime / MOV
+duration=83.500000
+size=12897484
+bit_rate=1235700
+[/FORMAT]
+";
+
+    fn read(output: &str) -> Result<MediaInfo, ExecutionError> {
+        #[cfg(feature = "serde")]
+        return MediaInfo::from_json(output);
+        #[cfg(not(feature = "serde"))]
+        return MediaInfo::from_default_output(output);
+    }
+
+    #[test]
+    fn test_read_probe_output() {
+        let info = read(SAMPLE).unwrap();
+
+        assert_eq!(info.path, PathBuf::from("video.mp4"));
+        assert_eq!(info.duration, Some(83.5));
+        assert_eq!(info.streams.len(), 2);
+        assert!(info.has_audio());
+        assert!(info.has_video());
+
+        let video = info.streams_of(StreamKind::Video).next().unwrap();
+        assert_eq!(video.codec.as_deref(), Some("h264"));
+        assert_eq!((video.width, video.height), (Some(1920), Some(1080)));
+        assert!((video.frame_rate.unwrap() - 29.97).abs() < 0.01);
+
+        let audio = info.streams_of(StreamKind::Audio).next().unwrap();
+        assert_eq!(audio.sample_rate, Some(48000));
+        assert_eq!(audio.frame_rate, None);
+        assert_eq!(audio.language.as_deref(), Some("eng"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let info = read(SAMPLE).unwrap();
+
+        assert_eq!(info.to_string(), "\
+File:      video.mp4
+Container: mov,mp4,m4a,3gp,3g2,mj2 (QuickTime / MOV)
+Duration:  00:01:23.500
+Size:      12.3 MB
+Bitrate:   1.2 Mb/s
+Streams:
+  #0 video: h264, 1920x1080, 29.97 fps, 4.5 Mb/s
+  #1 audio: aac, 48000 Hz, 2 channels, 128 kb/s, eng");
+    }
+
+    #[test]
+    fn test_read_probe_output_rejects_garbage() {
+        assert!(read("not ffprobe output").is_err());
+        assert!(read("").is_err());
+    }
+}
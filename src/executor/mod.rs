@@ -1,5 +1,12 @@
 //! Executor module for the FF CLI tool.
 //!
-//! This module handles the execution of generated ffmpeg commands.
+//! This module handles the execution of generated ffmpeg commands, alone or
+//! as a batch on a pool of workers, and the inspection of media files with
+//! ffprobe.
 
-pub mod runner;
\ No newline at end of file
+pub mod batch;
+pub mod media_info;
+pub mod probe;
+pub mod progress;
+pub mod queue;
+pub mod runner;
@@ -0,0 +1,227 @@
+use crate::executor::media_info::{MediaInfo, StreamInfo, StreamKind};
+use crate::executor::runner::ExecutionError;
+use crate::intent::params::{ConcatMethod, GifOptions, Quality};
+use crate::intent::types::{Intent, Operation};
+use crate::utils::codecs::{self, CodecKind};
+use crate::utils::containers;
+
+/// Returns whether two streams can be joined without re-encoding.
+///
+/// Bitrates, languages and frame rates may differ; codec, profile, frame
+/// size, pixel format and audio layout must match.
+fn streams_match(a: &StreamInfo, b: &StreamInfo) -> bool {
+    a.kind == b.kind
+        && a.codec == b.codec
+        && a.profile == b.profile
+        && a.width == b.width
+        && a.height == b.height
+        && a.pixel_format == b.pixel_format
+        && a.sample_rate == b.sample_rate
+        && a.channels == b.channels
+}
+
+/// Returns whether a container can hold every video and audio stream of a
+/// file as it is, so that the streams can be copied into it.
+///
+/// A codec ffrs does not know counts as not fitting, unless the container
+/// is one whose codecs are not checked.
+fn container_holds(extension: &str, info: &MediaInfo) -> bool {
+    let Some(container) = containers::lookup_container(extension) else {
+        return true;
+    };
+
+    info.streams.iter()
+        .filter(|stream| matches!(stream.kind, StreamKind::Video | StreamKind::Audio))
+        .all(|stream| {
+            stream.codec.as_deref()
+                .and_then(codecs::lookup_codec)
+                .is_some_and(|codec| container.holds(codec))
+        })
+}
+
+/// Chooses how to join inputs with the given streams into a file with the given extension.
+fn concat_method(infos: &[MediaInfo], extension: &str) -> ConcatMethod {
+    let all_match = infos.windows(2).all(|pair| {
+        pair[0].streams.len() == pair[1].streams.len()
+            && pair[0].streams.iter().zip(&pair[1].streams).all(|(a, b)| streams_match(a, b))
+    });
+    let fits = infos.first().is_some_and(|info| container_holds(extension, info));
+
+    match all_match && fits {
+        true => ConcatMethod::Demuxer,
+        false => ConcatMethod::Filter {
+            video: infos.iter().all(MediaInfo::has_video),
+            audio: infos.iter().all(MediaInfo::has_audio),
+        },
+    }
+}
+
+/// Picks how a `Concat` intent joins its inputs by inspecting them.
+///
+/// Chooses `ConcatMethod::Demuxer` when all inputs have the same stream
+/// layout and codecs and the output format can hold those codecs as they
+/// are. Otherwise the concat filter re-encodes them, leaving out the video
+/// or the audio when any input lacks a stream of that kind. Other intents are
+/// left as they are.
+///
+/// # Arguments
+///
+/// * `intent` - The intent to update
+///
+/// # Returns
+///
+/// A `Result` indicating success or an `ExecutionError` if an input could not be probed.
+pub fn choose_concat_method(intent: &mut Intent) -> Result<(), ExecutionError> {
+    if !matches!(intent.operation, Operation::Concat(_)) {
+        return Ok(());
+    }
+
+    let infos = intent.input_paths.iter()
+        .map(|path| MediaInfo::probe(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let extension = intent.output_path.extension().unwrap_or_default().to_string_lossy().into_owned();
+    intent.operation = Operation::Concat(concat_method(&infos, &extension));
+
+    Ok(())
+}
+
+/// Records the codec of audio that an intent copies without re-encoding, so
+/// that the builder can check the output format holds it.
+///
+/// The codec is `None` when the input's audio uses a codec ffrs does not
+/// know. Intents that copy no audio are left as they are.
+///
+/// # Arguments
+///
+/// * `intent` - The intent to update
+///
+/// # Returns
+///
+/// A `Result` indicating success or an `ExecutionError` if the input could not be probed.
+pub fn identify_copied_audio(intent: &mut Intent) -> Result<(), ExecutionError> {
+    let copies = |operation: &Operation| matches!(operation, Operation::ExtractAudio(Quality::Copy { source: None }));
+    let copied = match &intent.operation {
+        Operation::Pipeline(steps) => steps.iter().any(copies),
+        operation => copies(operation),
+    };
+    let Some(input_path) = intent.input_path().filter(|_| copied) else {
+        return Ok(());
+    };
+
+    let info = MediaInfo::probe(input_path)?;
+    let source = info.streams_of(StreamKind::Audio)
+        .next()
+        .and_then(|stream| stream.codec.as_deref())
+        .and_then(codecs::lookup_codec)
+        .filter(|codec| codec.kind == CodecKind::Audio);
+    let identified = Operation::ExtractAudio(Quality::Copy { source });
+
+    match &mut intent.operation {
+        Operation::Pipeline(steps) => {
+            for step in steps.iter_mut().filter(|step| copies(step)) {
+                *step = identified.clone();
+            }
+        },
+        operation => *operation = identified,
+    }
+
+    Ok(())
+}
+
+/// Estimates how long the output of an intent will be, in seconds.
+///
+/// Uses the intent's own time range when it fully determines the length,
+/// and otherwise probes the inputs. Returns `None` for single images and
+/// when the inputs cannot be probed.
+///
+/// # Arguments
+///
+/// * `intent` - The intent whose output length to estimate
+pub fn expected_duration(intent: &Intent) -> Option<f64> {
+    let input_duration = || MediaInfo::probe(intent.input_path()?).ok()?.duration;
+
+    match &intent.operation {
+        Operation::Thumbnail { .. } | Operation::Inspect => None,
+        Operation::Concat(_) => intent.input_paths.iter()
+            .map(|path| MediaInfo::probe(path).ok()?.duration)
+            .sum(),
+        Operation::Trim { range, .. } | Operation::Gif(GifOptions { range: Some(range), .. }) => {
+            range.length(input_duration)
+        },
+        Operation::Pipeline(steps) if steps.iter().any(|step| matches!(step, Operation::Thumbnail { .. })) => None,
+        Operation::Pipeline(steps) => {
+            let range = steps.iter().find_map(|step| match step {
+                Operation::Trim { range, .. } | Operation::Gif(GifOptions { range: Some(range), .. }) => Some(range),
+                _ => None,
+            });
+            match range {
+                Some(range) => range.length(input_duration),
+                None => input_duration(),
+            }
+        },
+        _ => input_duration(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn stream(index: usize, kind: StreamKind, codec: &str) -> StreamInfo {
+        StreamInfo {
+            index,
+            kind,
+            codec: Some(codec.to_string()),
+            profile: None,
+            width: None,
+            height: None,
+            pixel_format: None,
+            frame_rate: None,
+            sample_rate: None,
+            channels: None,
+            bit_rate: None,
+            language: None,
+        }
+    }
+
+    fn media(name: &str, codecs: &[(StreamKind, &str)]) -> MediaInfo {
+        MediaInfo {
+            path: PathBuf::from(name),
+            container: None,
+            container_long_name: None,
+            duration: None,
+            bit_rate: None,
+            size: None,
+            streams: codecs.iter().enumerate().map(|(index, (kind, codec))| stream(index, *kind, codec)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_concat_method_copies_only_codecs_the_output_holds() {
+        let vorbis = [(StreamKind::Video, "h264"), (StreamKind::Audio, "vorbis")];
+        let inputs = [media("a.mkv", &vorbis), media("b.mkv", &vorbis)];
+
+        assert_eq!(concat_method(&inputs, "mkv"), ConcatMethod::Demuxer);
+        assert_eq!(concat_method(&inputs, "mp4"), ConcatMethod::Filter { video: true, audio: true });
+        assert_eq!(concat_method(&inputs, "mp3"), ConcatMethod::Filter { video: true, audio: true });
+
+        let aac = [(StreamKind::Video, "h264"), (StreamKind::Audio, "aac")];
+        let inputs = [media("a.mkv", &aac), media("b.mkv", &aac)];
+        assert_eq!(concat_method(&inputs, "mp4"), ConcatMethod::Demuxer);
+
+        let unknown = [(StreamKind::Video, "prores"), (StreamKind::Audio, "aac")];
+        let inputs = [media("a.mov", &unknown), media("b.mov", &unknown)];
+        assert_eq!(concat_method(&inputs, "mp4"), ConcatMethod::Filter { video: true, audio: true });
+    }
+
+    #[test]
+    fn test_concat_method_leaves_out_streams_an_input_lacks() {
+        let inputs = [
+            media("a.mp4", &[(StreamKind::Video, "h264"), (StreamKind::Audio, "aac")]),
+            media("b.mp4", &[(StreamKind::Video, "h264")]),
+        ];
+        assert_eq!(concat_method(&inputs, "mp4"), ConcatMethod::Filter { video: true, audio: false });
+    }
+}
@@ -0,0 +1,217 @@
+use crate::utils::file_utils::format_size;
+use crate::utils::time::{format_timestamp, parse_timestamp};
+use std::io::Write;
+
+/// A snapshot of a running ffmpeg job.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Progress {
+    /// How far into the output ffmpeg has written, in seconds
+    pub out_time: Option<f64>,
+    /// The expected length of the output in seconds, when known
+    pub duration: Option<f64>,
+    /// Processing speed as a multiple of real time
+    pub speed: Option<f64>,
+    /// The number of bytes written to the output so far
+    pub total_size: Option<u64>,
+    /// Whether ffmpeg reported that the job is done
+    pub finished: bool,
+}
+
+impl Progress {
+    /// Returns the completed share of the job from 0 to 100, when the duration is known.
+    pub fn percent(&self) -> Option<f64> {
+        if self.finished {
+            return Some(100.0);
+        }
+        let (out_time, duration) = (self.out_time?, self.duration?);
+        (duration > 0.0).then(|| (out_time / duration * 100.0).clamp(0.0, 100.0))
+    }
+
+    /// Returns the estimated seconds remaining, from the duration and current speed.
+    pub fn eta(&self) -> Option<f64> {
+        if self.finished {
+            return Some(0.0);
+        }
+        let (out_time, duration, speed) = (self.out_time?, self.duration?, self.speed?);
+        (speed > 0.0).then(|| ((duration - out_time) / speed).max(0.0))
+    }
+}
+
+/// Receives progress updates from a running job.
+pub trait ProgressReporter {
+    /// Called each time ffmpeg reports a new block of progress.
+    fn report(&mut self, progress: &Progress);
+}
+
+impl<F: FnMut(&Progress)> ProgressReporter for F {
+    fn report(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+/// Turns the `key=value` lines of `ffmpeg -progress` into `Progress` snapshots.
+///
+/// ffmpeg writes a block of keys and closes each block with a `progress=`
+/// line, so a snapshot is produced once per block.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressParser {
+    current: Progress,
+}
+
+impl ProgressParser {
+    /// Creates a parser for a job whose output is expected to last `duration` seconds.
+    pub fn new(duration: Option<f64>) -> Self {
+        Self {
+            current: Progress {
+                duration,
+                ..Progress::default()
+            },
+        }
+    }
+
+    /// Feeds one line of progress output.
+    ///
+    /// # Returns
+    ///
+    /// The completed snapshot when the line closes a block, `None` otherwise.
+    pub fn feed_line(&mut self, line: &str) -> Option<Progress> {
+        let (key, value) = line.trim().split_once('=')?;
+        let value = value.trim();
+
+        match key {
+            "out_time_us" | "out_time_ms" => {
+                // Despite its name, out_time_ms is also in microseconds.
+                if let Ok(micros) = value.parse::<i64>() {
+                    self.current.out_time = Some(micros.max(0) as f64 / 1_000_000.0);
+                }
+            },
+            "out_time" => {
+                if let Some(seconds) = parse_timestamp(value) {
+                    self.current.out_time = Some(seconds);
+                }
+            },
+            "total_size" => self.current.total_size = value.parse().ok(),
+            "speed" => self.current.speed = value.trim_end_matches('x').trim().parse().ok(),
+            "progress" => {
+                self.current.finished = value == "end";
+                return Some(self.current.clone());
+            },
+            _ => {},
+        }
+
+        None
+    }
+}
+
+/// Writes progress to a console, as a redrawn bar or as plain lines.
+///
+/// The bar suits interactive terminals; line mode suits logs and pipes,
+/// where carriage returns would pile up.
+#[derive(Debug)]
+pub struct ConsoleProgress<W: Write> {
+    out: W,
+    bar: bool,
+}
+
+impl<W: Write> ConsoleProgress<W> {
+    /// Creates a reporter writing to `out`, drawing a bar when `bar` is true.
+    pub fn new(out: W, bar: bool) -> Self {
+        Self { out, bar }
+    }
+
+    /// Renders a snapshot as a single line without any terminal control characters.
+    pub fn render(progress: &Progress, bar: bool) -> String {
+        const WIDTH: usize = 30;
+
+        let mut parts = Vec::new();
+        match progress.percent() {
+            Some(percent) if bar => {
+                let filled = (percent / 100.0 * WIDTH as f64).round() as usize;
+                parts.push(format!("[{}{}] {:5.1}%", "#".repeat(filled), "-".repeat(WIDTH - filled), percent));
+            },
+            Some(percent) => parts.push(format!("{:.1}%", percent)),
+            None => parts.push(format_timestamp(progress.out_time.unwrap_or_default())),
+        }
+        if let Some(speed) = progress.speed {
+            parts.push(format!("speed {:.2}x", speed));
+        }
+        if let Some(eta) = progress.eta() {
+            parts.push(format!("ETA {}", format_timestamp(eta.round())));
+        }
+        if let Some(size) = progress.total_size {
+            parts.push(format_size(size));
+        }
+
+        parts.join("  ")
+    }
+}
+
+impl<W: Write> ProgressReporter for ConsoleProgress<W> {
+    fn report(&mut self, progress: &Progress) {
+        let line = Self::render(progress, self.bar);
+        let _ = if self.bar {
+            let end = if progress.finished { "\n" } else { "" };
+            write!(self.out, "\r\x1b[2K{}{}", line, end)
+        } else {
+            writeln!(self.out, "{}", line)
+        };
+        let _ = self.out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser_emits_one_snapshot_per_block() {
+        let mut parser = ProgressParser::new(Some(100.0));
+        let lines = [
+            "frame=250", "out_time_us=25000000", "out_time=00:00:25.000000",
+            "total_size=1048576", "speed=2.5x", "progress=continue",
+        ];
+
+        let snapshots: Vec<_> = lines.iter().filter_map(|line| parser.feed_line(line)).collect();
+        assert_eq!(snapshots.len(), 1);
+
+        let progress = &snapshots[0];
+        assert_eq!(progress.out_time, Some(25.0));
+        assert_eq!(progress.total_size, Some(1048576));
+        assert_eq!(progress.percent(), Some(25.0));
+        assert_eq!(progress.eta(), Some(30.0));
+        assert!(!progress.finished);
+
+        let end = parser.feed_line("progress=end").unwrap();
+        assert!(end.finished);
+        assert_eq!(end.percent(), Some(100.0));
+    }
+
+    #[test]
+    fn test_parser_tolerates_unknown_values() {
+        let mut parser = ProgressParser::new(None);
+        parser.feed_line("out_time_us=N/A");
+        parser.feed_line("speed=N/A");
+
+        let progress = parser.feed_line("progress=continue").unwrap();
+        assert_eq!(progress.out_time, None);
+        assert_eq!(progress.speed, None);
+        assert_eq!(progress.percent(), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let progress = Progress {
+            out_time: Some(25.0),
+            duration: Some(100.0),
+            speed: Some(2.5),
+            total_size: Some(1048576),
+            finished: false,
+        };
+
+        assert_eq!(
+            ConsoleProgress::<Vec<u8>>::render(&progress, false),
+            "25.0%  speed 2.50x  ETA 00:00:30  1.0 MB"
+        );
+        assert!(ConsoleProgress::<Vec<u8>>::render(&progress, true).starts_with("[########------"));
+    }
+}
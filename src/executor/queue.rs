@@ -0,0 +1,233 @@
+use crate::command_builder::command::FfmpegCommand;
+use crate::executor::batch::{BatchSummary, JobStatus};
+use crate::executor::runner::{ExecutionError, Runner};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// The number of cores a single ffmpeg process is assumed to keep busy.
+///
+/// Most encoders are multi-threaded themselves, so running one job per core
+/// would oversubscribe the machine.
+pub const THREADS_PER_FFMPEG: usize = 4;
+
+/// One command of a batch, ready to run.
+#[derive(Debug, Clone)]
+pub struct Job {
+    /// A short description for status lines and the summary, such as `in.mov -> out.mp4`
+    pub label: String,
+    /// The command to run
+    pub command: FfmpegCommand,
+    /// The expected output length in seconds, for progress reporting
+    pub duration: Option<f64>,
+}
+
+/// What the queue does once a job fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FailureMode {
+    /// Run every remaining job regardless
+    #[default]
+    KeepGoing,
+    /// Start no new jobs; the ones already running are allowed to finish
+    FailFast,
+}
+
+/// A change in the state of a job, reported while the queue runs.
+#[derive(Debug)]
+pub enum JobEvent<'a> {
+    /// The job at `index` was handed to a worker
+    Started { index: usize, job: &'a Job },
+    /// The job at `index` ended with `status`
+    Finished { index: usize, job: &'a Job, status: &'a JobStatus },
+}
+
+/// Runs batch jobs on a bounded pool of worker threads.
+#[derive(Debug, Clone)]
+pub struct JobQueue {
+    workers: usize,
+    failure_mode: FailureMode,
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new(Self::default_workers())
+    }
+}
+
+impl JobQueue {
+    /// Creates a queue that runs at most `workers` jobs at a time and keeps going on failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `workers` - The largest number of concurrent jobs; zero is treated as one
+    pub fn new(workers: usize) -> Self {
+        Self {
+            workers: workers.max(1),
+            failure_mode: FailureMode::default(),
+        }
+    }
+
+    /// Returns the default worker count: the number of CPUs divided by `THREADS_PER_FFMPEG`.
+    pub fn default_workers() -> usize {
+        let cpus = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        (cpus / THREADS_PER_FFMPEG).max(1)
+    }
+
+    /// Sets what happens after a job fails.
+    pub fn failure_mode(mut self, failure_mode: FailureMode) -> Self {
+        self.failure_mode = failure_mode;
+        self
+    }
+
+    /// Returns the largest number of jobs that run at the same time.
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Runs the jobs with `Runner::execute`.
+    ///
+    /// # Arguments
+    ///
+    /// * `runner` - The runner that executes each job, with its overwrite policy
+    /// * `jobs` - The jobs to run, in the order they should be started
+    /// * `on_event` - Called from the worker threads as jobs start and finish
+    ///
+    /// # Returns
+    ///
+    /// A `BatchSummary` listing every job in its original order.
+    pub fn run<E>(&self, runner: &Runner, jobs: &[Job], on_event: E) -> BatchSummary
+    where
+        E: Fn(JobEvent<'_>) + Sync,
+    {
+        self.run_with(jobs, |job| runner.execute(&job.command), on_event)
+    }
+
+    /// Runs the jobs with a custom executor, such as one that reports progress.
+    ///
+    /// Jobs that fail with `ExecutionError::OutputExists` count as skipped.
+    /// In fail-fast mode, jobs that had not started when a failure happened
+    /// are marked skipped too.
+    pub fn run_with<X, E>(&self, jobs: &[Job], execute: X, on_event: E) -> BatchSummary
+    where
+        X: Fn(&Job) -> Result<(), ExecutionError> + Sync,
+        E: Fn(JobEvent<'_>) + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let cancelled = AtomicBool::new(false);
+        let statuses: Mutex<Vec<Option<JobStatus>>> = Mutex::new(vec![None; jobs.len()]);
+
+        thread::scope(|scope| {
+            for _ in 0..self.workers.min(jobs.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(job) = jobs.get(index) else {
+                        break;
+                    };
+
+                    let status = if cancelled.load(Ordering::SeqCst) {
+                        JobStatus::Skipped("cancelled after an earlier failure".to_string())
+                    } else {
+                        on_event(JobEvent::Started { index, job });
+                        match execute(job) {
+                            Ok(()) => JobStatus::Succeeded,
+                            Err(ExecutionError::OutputExists(_)) => {
+                                JobStatus::Skipped("output already exists".to_string())
+                            },
+                            Err(e) => {
+                                if self.failure_mode == FailureMode::FailFast {
+                                    cancelled.store(true, Ordering::SeqCst);
+                                }
+                                JobStatus::Failed(e.to_string())
+                            },
+                        }
+                    };
+
+                    on_event(JobEvent::Finished { index, job, status: &status });
+                    statuses.lock().unwrap()[index] = Some(status);
+                });
+            }
+        });
+
+        let mut summary = BatchSummary::new();
+        for (job, status) in jobs.iter().zip(statuses.into_inner().unwrap()) {
+            summary.record(job.label.clone(), status.expect("every job is visited by a worker"));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+    use std::time::Duration;
+
+    fn jobs(count: usize) -> Vec<Job> {
+        (0..count)
+            .map(|i| {
+                let mut command = FfmpegCommand::new("ffmpeg");
+                command.arg("-i").arg(format!("in{}.mov", i)).output(format!("/nonexistent/out{}.mp4", i));
+                Job { label: format!("job {}", i), command, duration: None }
+            })
+            .collect()
+    }
+
+    fn fail_odd(job: &Job) -> Result<(), ExecutionError> {
+        if job.label.ends_with(['1', '3', '5', '7', '9']) {
+            Err(ExecutionError::CommandFailed("exit status 1".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_existing_output_counts_as_skipped() {
+        let execute = |job: &Job| Err(ExecutionError::OutputExists(job.command.get_output().unwrap().into()));
+        let summary = JobQueue::new(2).run_with(&jobs(2), execute, |_| {});
+
+        assert_eq!((summary.succeeded(), summary.failed(), summary.skipped()), (0, 0, 2));
+    }
+
+    #[test]
+    fn test_keep_going_runs_every_job_in_order() {
+        let summary = JobQueue::new(3).run_with(&jobs(6), fail_odd, |_| {});
+
+        assert_eq!((summary.succeeded(), summary.failed(), summary.skipped()), (3, 3, 0));
+        assert!(summary.to_string().lines().nth(1).unwrap().starts_with("job 0"));
+    }
+
+    #[test]
+    fn test_fail_fast_skips_jobs_not_yet_started() {
+        let queue = JobQueue::new(1).failure_mode(FailureMode::FailFast);
+        let summary = queue.run_with(&jobs(5), fail_odd, |_| {});
+
+        assert_eq!((summary.succeeded(), summary.failed(), summary.skipped()), (1, 1, 3));
+    }
+
+    #[test]
+    fn test_workers_bound_concurrency() {
+        let peak_with = |workers: usize, barrier: Option<Barrier>| {
+            let running = AtomicUsize::new(0);
+            let peak = AtomicUsize::new(0);
+            let execute = |_: &Job| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                // Each job waits for another to start, so the workers must overlap.
+                if let Some(barrier) = &barrier {
+                    barrier.wait();
+                }
+                thread::sleep(Duration::from_millis(20));
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            };
+
+            let summary = JobQueue::new(workers).run_with(&jobs(6), execute, |_| {});
+            assert_eq!(summary.succeeded(), 6);
+            peak.load(Ordering::SeqCst)
+        };
+
+        assert_eq!(peak_with(2, Some(Barrier::new(2))), 2);
+        assert_eq!(peak_with(1, None), 1);
+    }
+}
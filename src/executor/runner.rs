@@ -1,8 +1,37 @@
-use std::process::Command;
+use crate::command_builder::command::FfmpegCommand;
+use crate::executor::progress::{ProgressParser, ProgressReporter};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// What to do when a command's output file already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// Leave the existing file alone and report `ExecutionError::OutputExists`
+    #[default]
+    NoClobber,
+    /// Replace the existing file
+    Overwrite,
+    /// Write to the first free name of the form `name (1).mp4`
+    Rename,
+}
 
 /// Runner for executing ffmpeg commands.
-#[derive(Debug)]
-pub struct Runner;
+///
+/// Output is first written to a hidden partial file next to the final one
+/// and renamed into place only when ffmpeg succeeds, so a failed or
+/// interrupted job never leaves a truncated file behind that looks complete.
+/// Each job gets its own partial file, even when jobs share an output.
+/// Unless overwriting, a file that appears at the output meanwhile is kept
+/// and the partial file is left next to it. Under `OverwritePolicy::Rename`
+/// the free name is claimed when the job starts, so jobs running at the same
+/// time never pick the same one. Image sequences such as `frame_%04d.jpg` are written in place.
+#[derive(Debug, Default)]
+pub struct Runner {
+    overwrite_policy: OverwritePolicy,
+}
 
 /// Error types that can occur during command execution.
 #[derive(Debug)]
@@ -11,6 +40,8 @@ pub enum ExecutionError {
     CommandFailed(String),
     /// The command is invalid
     InvalidCommand(String),
+    /// The output file exists and the overwrite policy forbids replacing it
+    OutputExists(PathBuf),
 }
 
 impl std::fmt::Display for ExecutionError {
@@ -18,6 +49,7 @@ impl std::fmt::Display for ExecutionError {
         match self {
             ExecutionError::CommandFailed(msg) => write!(f, "Command failed: {}", msg),
             ExecutionError::InvalidCommand(cmd) => write!(f, "Invalid command: {}", cmd),
+            ExecutionError::OutputExists(path) => write!(f, "Output file already exists: {}", path.display()),
         }
     }
 }
@@ -27,7 +59,39 @@ impl std::error::Error for ExecutionError {}
 impl Runner {
     /// Creates a new command runner.
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Sets what happens when an output file already exists.
+    pub fn overwrite_policy(mut self, overwrite_policy: OverwritePolicy) -> Self {
+        self.overwrite_policy = overwrite_policy;
+        self
+    }
+
+    /// Applies the overwrite policy to a command.
+    ///
+    /// The returned command carries `-y` or `-n` so ffmpeg never prompts,
+    /// and under `OverwritePolicy::Rename` writes to the first free name.
+    /// Resolving an already resolved command changes nothing.
+    ///
+    /// # Returns
+    ///
+    /// The resolved command, or `ExecutionError::OutputExists` when the
+    /// output exists and the policy is `OverwritePolicy::NoClobber`.
+    pub fn resolve_output(&self, cmd: &FfmpegCommand) -> Result<FfmpegCommand, ExecutionError> {
+        let mut resolved = cmd.clone();
+        let overwrite = self.overwrite_policy == OverwritePolicy::Overwrite;
+
+        if let Some(output) = cmd.get_output().filter(|output| output.exists()) {
+            match self.overwrite_policy {
+                OverwritePolicy::NoClobber => return Err(ExecutionError::OutputExists(output.to_path_buf())),
+                OverwritePolicy::Overwrite => {},
+                OverwritePolicy::Rename => resolved = cmd.with_output(free_name(output)),
+            }
+        }
+
+        resolved.set_overwrite(overwrite);
+        Ok(resolved)
     }
 
     /// Executes the given ffmpeg command.
@@ -39,20 +103,18 @@ impl Runner {
     /// # Returns
     ///
     /// A `Result` indicating success or an `ExecutionError`.
-    pub fn execute(&self, cmd: &str) -> Result<(), ExecutionError> {
-        self.check_ffmpeg_availability()?;
-
-        let parts: Vec<&str> = cmd.split_whitespace().collect();
-
-        if parts.is_empty() {
+    pub fn execute(&self, cmd: &FfmpegCommand) -> Result<(), ExecutionError> {
+        if cmd.get_program().is_empty() {
             return Err(ExecutionError::InvalidCommand("Command is empty".to_string()));
         }
 
-        let program = parts[0];
-        let args: Vec<&str> = parts[1..].to_vec();
+        self.check_ffmpeg_availability()?;
+        self.run_atomically(cmd, |cmd| self.run_to_completion(cmd))
+    }
 
-        let output = Command::new(program)
-            .args(&args)
+    fn run_to_completion(&self, cmd: &FfmpegCommand) -> Result<(), ExecutionError> {
+        let output = cmd.to_command()
+            .stdin(Stdio::null())
             .output()
             .map_err(|e| ExecutionError::CommandFailed(format!("Failed to execute command: {}", e)))?;
 
@@ -72,6 +134,159 @@ impl Runner {
         }
     }
 
+    /// Executes the given ffmpeg command while reporting its progress.
+    ///
+    /// ffmpeg is started with `-progress pipe:1 -nostats` and its progress
+    /// stream is parsed as it arrives. Its log output is collected and printed
+    /// only if the command fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The ffmpeg command to execute
+    /// * `duration` - The expected output length in seconds, used for percentages and ETA
+    /// * `reporter` - Receives a `Progress` snapshot for each progress block
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or an `ExecutionError`.
+    pub fn execute_with_progress(
+        &self,
+        cmd: &FfmpegCommand,
+        duration: Option<f64>,
+        reporter: &mut dyn ProgressReporter,
+    ) -> Result<(), ExecutionError> {
+        if cmd.get_program().is_empty() {
+            return Err(ExecutionError::InvalidCommand("Command is empty".to_string()));
+        }
+
+        self.check_ffmpeg_availability()?;
+        self.run_atomically(cmd, |cmd| self.run_with_progress(cmd, duration, reporter))
+    }
+
+    /// Resolves the output, runs the command against a partial file and
+    /// moves that file into place once `run` succeeds.
+    fn run_atomically<F>(&self, cmd: &FfmpegCommand, run: F) -> Result<(), ExecutionError>
+    where
+        F: FnOnce(&FfmpegCommand) -> Result<(), ExecutionError>,
+    {
+        let mut resolved = self.resolve_output(cmd)?;
+        let Some(output) = resolved.get_output().filter(|output| !is_sequence(output)).map(Path::to_path_buf) else {
+            self.prepare(&resolved)?;
+            let result = run(&resolved);
+            self.clean_up(&resolved);
+            return result;
+        };
+        create_parent(&output)?;
+
+        let reserved = match self.overwrite_policy {
+            OverwritePolicy::Rename => {
+                let reserved = reserve(&output)?;
+                if reserved != output {
+                    eprintln!("Note: {} was taken meanwhile; writing {} instead.", output.display(), reserved.display());
+                    resolved = resolved.with_output(&reserved);
+                }
+                Some(reserved)
+            },
+            _ => None,
+        };
+        let output = reserved.clone().unwrap_or(output);
+        let partial = match create_partial(&output) {
+            Ok(partial) => partial,
+            Err(e) => {
+                remove_reservation(reserved.as_deref());
+                return Err(e);
+            },
+        };
+
+        let mut working = resolved.with_output(&partial);
+        // The partial file was just created for this job, so ffmpeg may write over it.
+        working.set_overwrite(true);
+
+        let result = self.prepare(&working).and_then(|_| run(&working));
+        self.clean_up(&working);
+
+        let result = match result {
+            Ok(()) => publish(&partial, &output, self.overwrite_policy != OverwritePolicy::NoClobber),
+            Err(e) => {
+                let _ = std::fs::remove_file(&partial);
+                Err(e)
+            },
+        };
+        if result.is_err() {
+            remove_reservation(reserved.as_deref());
+        }
+        result
+    }
+
+    fn run_with_progress(
+        &self,
+        cmd: &FfmpegCommand,
+        duration: Option<f64>,
+        reporter: &mut dyn ProgressReporter,
+    ) -> Result<(), ExecutionError> {
+        let mut child = Command::new(cmd.get_program())
+            .args(["-progress", "pipe:1", "-nostats"])
+            .args(cmd.get_args())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| ExecutionError::CommandFailed(format!("Failed to execute command: {}", e)))?;
+
+        // Drain stderr on its own thread so a chatty ffmpeg never blocks on a full pipe.
+        let stderr = child.stderr.take().map(|mut stderr| {
+            thread::spawn(move || {
+                let mut log = String::new();
+                let _ = stderr.read_to_string(&mut log);
+                log
+            })
+        });
+
+        let mut parser = ProgressParser::new(duration);
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some(progress) = parser.feed_line(&line) {
+                    reporter.report(&progress);
+                }
+            }
+        }
+
+        let status = child.wait()
+            .map_err(|e| ExecutionError::CommandFailed(format!("Failed to wait for command: {}", e)))?;
+        let log = stderr.and_then(|handle| handle.join().ok()).unwrap_or_default();
+
+        if status.success() {
+            Ok(())
+        } else {
+            if !log.is_empty() {
+                eprintln!("{}", log.trim_end());
+            }
+            Err(ExecutionError::CommandFailed(format!("Command exited with status: {}", status)))
+        }
+    }
+
+    /// Creates the output directory and writes the command's support files.
+    fn prepare(&self, cmd: &FfmpegCommand) -> Result<(), ExecutionError> {
+        if let Some(output) = cmd.get_output() {
+            create_parent(output)?;
+        }
+
+        for (path, contents) in cmd.get_support_files() {
+            std::fs::write(path, contents).map_err(|e| ExecutionError::CommandFailed(
+                format!("Failed to write {}: {}", path.display(), e)
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the command's support files, ignoring any that are already gone.
+    fn clean_up(&self, cmd: &FfmpegCommand) {
+        for (path, _) in cmd.get_support_files() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
     /// Checks if ffmpeg is available in the system PATH.
     ///
     /// # Returns
@@ -88,6 +303,124 @@ impl Runner {
 
 }
 
+/// Counts the partial files this process has named, so that each is unique.
+static PARTIAL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns whether an output is an image sequence pattern such as `frame_%04d.jpg`.
+fn is_sequence(output: &Path) -> bool {
+    output.file_name().is_some_and(|name| name.to_string_lossy().contains('%'))
+}
+
+/// Creates the directory an output goes in, if it is missing.
+fn create_parent(output: &Path) -> Result<(), ExecutionError> {
+    match output.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() && !dir.exists() => {
+            std::fs::create_dir_all(dir).map_err(|e| ExecutionError::CommandFailed(
+                format!("Failed to create {}: {}", dir.display(), e)
+            ))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Creates a file that no other process or job has, failing if it exists.
+fn create_new(path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new().write(true).create_new(true).open(path)
+}
+
+/// Returns the hidden file an output is written to before it is complete,
+/// told apart from those of other jobs by the process id and `id`.
+///
+/// The extension is kept so ffmpeg still picks the right format.
+fn partial_path(output: &Path, id: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let partial = match output.extension() {
+        Some(extension) => format!(".{}.partial-{}-{}.{}", stem, std::process::id(), id, extension.to_string_lossy()),
+        None => format!(".{}.partial-{}-{}", stem, std::process::id(), id),
+    };
+    output.with_file_name(partial)
+}
+
+/// Creates an empty partial file for an output that no other job uses.
+fn create_partial(output: &Path) -> Result<PathBuf, ExecutionError> {
+    loop {
+        let partial = partial_path(output, PARTIAL_FILES.fetch_add(1, Ordering::Relaxed));
+        match create_new(&partial) {
+            Ok(_) => return Ok(partial),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(ExecutionError::CommandFailed(format!("Failed to create {}: {}", partial.display(), e)));
+            },
+        }
+    }
+}
+
+/// Claims the output, or the first free numbered name after it, by creating
+/// an empty file there, so that no other job can choose the same name.
+fn reserve(output: &Path) -> Result<PathBuf, ExecutionError> {
+    let mut candidate = output.to_path_buf();
+    loop {
+        match create_new(&candidate) {
+            Ok(_) => return Ok(candidate),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => candidate = free_name(output),
+            Err(e) => {
+                return Err(ExecutionError::CommandFailed(format!("Failed to create {}: {}", candidate.display(), e)));
+            },
+        }
+    }
+}
+
+/// Removes the empty file `reserve` left for a job that wrote nothing.
+fn remove_reservation(reserved: Option<&Path>) {
+    if let Some(reserved) = reserved.filter(|reserved| std::fs::metadata(reserved).is_ok_and(|meta| meta.len() == 0)) {
+        let _ = std::fs::remove_file(reserved);
+    }
+}
+
+/// Moves a finished partial file to its output.
+///
+/// Unless `replace` is set, an output that appeared while ffmpeg was running
+/// is left alone and the partial file is kept for the user. Hard-linking
+/// fails atomically when the output exists; on filesystems without hard
+/// links, the output is checked just before renaming instead.
+fn publish(partial: &Path, output: &Path, replace: bool) -> Result<(), ExecutionError> {
+    let appeared = || ExecutionError::CommandFailed(format!(
+        "{} was created while ffmpeg was running and was left alone; the new output is in {}",
+        output.display(),
+        partial.display()
+    ));
+
+    let moved = if replace {
+        std::fs::rename(partial, output)
+    } else {
+        match std::fs::hard_link(partial, output) {
+            Ok(()) => {
+                let _ = std::fs::remove_file(partial);
+                return Ok(());
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Err(appeared()),
+            Err(_) if output.exists() => return Err(appeared()),
+            Err(_) => std::fs::rename(partial, output),
+        }
+    };
+
+    moved.map_err(|e| {
+        let _ = std::fs::remove_file(partial);
+        ExecutionError::CommandFailed(format!("Failed to move output into {}: {}", output.display(), e))
+    })
+}
+
+/// Returns the first name of the form `name (1).ext` that does not exist yet.
+fn free_name(output: &Path) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = output.extension().map(|extension| format!(".{}", extension.to_string_lossy()));
+
+    (1..)
+        .map(|n| output.with_file_name(format!("{} ({}){}", stem, n, extension.as_deref().unwrap_or(""))))
+        .find(|candidate| !candidate.exists())
+        .expect("some numbered name is free")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,6 +428,128 @@ mod tests {
     #[test]
     fn test_runner_creation() {
         let runner = Runner::new();
-        assert_eq!(format!("{:?}", runner), "Runner");
+        assert_eq!(format!("{:?}", runner), "Runner { overwrite_policy: NoClobber }");
+    }
+
+    #[test]
+    fn test_execute_rejects_empty_program() {
+        let runner = Runner::new();
+        let result = runner.execute(&FfmpegCommand::new(""));
+        assert!(matches!(result, Err(ExecutionError::InvalidCommand(_))));
     }
-}
\ No newline at end of file
+
+    fn command_writing(output: &Path) -> FfmpegCommand {
+        let mut cmd = FfmpegCommand::new("ffmpeg");
+        cmd.arg("-i").arg("in.mp4").output(output);
+        cmd
+    }
+
+    #[test]
+    fn test_resolve_output_applies_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("clip.mp4");
+        std::fs::write(&output, b"existing").unwrap();
+        std::fs::write(dir.path().join("clip (1).mp4"), b"existing").unwrap();
+        let cmd = command_writing(&output);
+
+        let result = Runner::new().resolve_output(&cmd);
+        assert!(matches!(result, Err(ExecutionError::OutputExists(path)) if path == output));
+
+        let overwrite = Runner::new().overwrite_policy(OverwritePolicy::Overwrite).resolve_output(&cmd).unwrap();
+        assert_eq!(overwrite.get_args()[0], "-y");
+        assert_eq!(overwrite.get_output(), Some(output.as_path()));
+
+        let rename = Runner::new().overwrite_policy(OverwritePolicy::Rename).resolve_output(&cmd).unwrap();
+        assert_eq!(rename.get_args()[0], "-n");
+        assert_eq!(rename.get_output(), Some(dir.path().join("clip (2).mp4").as_path()));
+        assert_eq!(Runner::new().resolve_output(&rename).unwrap(), rename);
+    }
+
+    /// Returns the partial files left in a directory.
+    fn partial_files(dir: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.file_name().unwrap().to_string_lossy().contains(".partial-"))
+            .collect()
+    }
+
+    #[test]
+    fn test_output_created_mid_run_is_not_clobbered() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("clip.mp4");
+        let cmd = command_writing(&output);
+        let run = |working: &FfmpegCommand| {
+            std::fs::write(working.get_output().unwrap(), b"new").unwrap();
+            std::fs::write(&output, b"theirs").unwrap();
+            Ok(())
+        };
+
+        let error = Runner::new().run_atomically(&cmd, run).unwrap_err().to_string();
+        assert!(error.contains("was created while ffmpeg was running and was left alone"), "{}", error);
+        assert_eq!(std::fs::read(&output).unwrap(), b"theirs");
+        let partials = partial_files(dir.path());
+        assert_eq!(partials.len(), 1);
+        assert_eq!(std::fs::read(&partials[0]).unwrap(), b"new");
+        std::fs::remove_file(&partials[0]).unwrap();
+
+        std::fs::remove_file(&output).unwrap();
+        Runner::new().run_atomically(&cmd, |working| {
+            std::fs::write(working.get_output().unwrap(), b"new").unwrap();
+            Ok(())
+        }).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), b"new");
+        assert!(partial_files(dir.path()).is_empty());
+
+        std::fs::remove_file(&output).unwrap();
+        let overwrite = Runner::new().overwrite_policy(OverwritePolicy::Overwrite);
+        overwrite.run_atomically(&cmd, run).unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), b"new");
+        assert!(partial_files(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_jobs_sharing_an_output_get_their_own_partial_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("clip.mp4");
+
+        let first = create_partial(&output).unwrap();
+        let second = create_partial(&output).unwrap();
+        assert_ne!(first, second);
+        assert!(first.exists() && second.exists());
+        assert!(first.file_name().unwrap().to_string_lossy().starts_with(".clip.partial-"));
+        assert_eq!(first.extension().unwrap(), "mp4");
+    }
+
+    #[test]
+    fn test_rename_claims_the_name_while_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("clip.mp4");
+        let cmd = command_writing(&output);
+        let runner = Runner::new().overwrite_policy(OverwritePolicy::Rename);
+        let write = |contents: &'static [u8]| move |working: &FfmpegCommand| {
+            std::fs::write(working.get_output().unwrap(), contents).map_err(|e| ExecutionError::CommandFailed(e.to_string()))
+        };
+
+        // A second job starting while the first runs must not pick the same name.
+        runner.run_atomically(&cmd, |working| {
+            assert!(output.exists());
+            runner.run_atomically(&cmd, write(b"second"))?;
+            write(b"first")(working)
+        }).unwrap();
+
+        assert_eq!(std::fs::read(&output).unwrap(), b"first");
+        assert_eq!(std::fs::read(dir.path().join("clip (1).mp4")).unwrap(), b"second");
+        assert!(partial_files(dir.path()).is_empty());
+
+        let result = runner.run_atomically(&cmd, |_| Err(ExecutionError::CommandFailed("failed".to_string())));
+        assert!(result.is_err());
+        assert!(!dir.path().join("clip (2).mp4").exists());
+    }
+
+    #[test]
+    fn test_partial_path() {
+        let partial = partial_path(Path::new("out/clip.mp4"), 3);
+        assert_eq!(partial, PathBuf::from(format!("out/.clip.partial-{}-3.mp4", std::process::id())));
+        assert!(is_sequence(Path::new("frames/clip_%04d.jpg")));
+    }
+}
@@ -0,0 +1,620 @@
+use crate::command_builder::builder::{audio_encoding, concat_filter, gif_filter};
+use crate::grammar::parser::{parse_dimension, parse_frame_rate};
+use crate::intent::params::{
+    Bitrate, ConcatMethod, FrameRate, GifOptions, Pixels, Quality, Resolution, TimePoint, TimeRange, TimeSpan, TrimMode,
+};
+use crate::intent::types::{Intent, Operation};
+use crate::utils::codecs::{self, CodecKind};
+use crate::utils::time;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// An existing ffmpeg command read back into an intent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Description {
+    /// What the command does, as far as an intent can express it
+    pub intent: Intent,
+    /// The parts of the command the intent leaves out, as written (e.g., `-crf 28`)
+    pub unsupported: Vec<String>,
+}
+
+impl Description {
+    /// Returns the intent as a sentence that `Parser` accepts.
+    pub fn sentence(&self) -> String {
+        sentence(&self.intent)
+    }
+}
+
+/// Error types that can occur while reading an ffmpeg command.
+#[derive(Debug)]
+pub enum DescribeError {
+    /// The command runs a program other than ffmpeg
+    NotFfmpeg(String),
+    /// A quote was opened but never closed
+    UnterminatedQuote,
+    /// An option was given without its value
+    MissingValue(String),
+    /// The command names no input file
+    MissingInput,
+    /// The command names no output file
+    MissingOutput,
+}
+
+impl std::fmt::Display for DescribeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DescribeError::NotFfmpeg(program) => write!(f, "Not an ffmpeg command: {}", program),
+            DescribeError::UnterminatedQuote => write!(f, "Unterminated quote in the command"),
+            DescribeError::MissingValue(option) => write!(f, "Missing value for option: {}", option),
+            DescribeError::MissingInput => write!(f, "The command has no input file (-i)"),
+            DescribeError::MissingOutput => write!(f, "The command has no output file"),
+        }
+    }
+}
+
+impl std::error::Error for DescribeError {}
+
+/// Options that never take a value.
+const FLAGS: &[&str] = &[
+    "-y", "-n", "-hide_banner", "-nostdin", "-stats", "-nostats", "-vn", "-an", "-sn", "-dn", "-shortest", "-re",
+];
+
+/// Options that only change what ffmpeg prints or asks, which a description can leave out.
+const QUIET: &[&str] = &["-n", "-hide_banner", "-nostdin", "-stats", "-nostats", "-loglevel", "-v", "-progress"];
+
+const VIDEO_FILTER: &[&str] = &["-vf", "-filter:v"];
+const VIDEO_CODEC: &[&str] = &["-c:v", "-codec:v", "-vcodec"];
+const AUDIO_CODEC: &[&str] = &["-c:a", "-codec:a", "-acodec"];
+const AUDIO_BITRATE: &[&str] = &["-b:a", "-ab"];
+const STREAM_COPY: &[&str] = &["-c", "-codec"];
+const FRAMES: &[&str] = &["-frames:v", "-vframes"];
+
+/// Reads an ffmpeg command line, as it would be typed into a shell.
+///
+/// # Arguments
+///
+/// * `command_line` - The command, starting with `ffmpeg`; single and double
+///   quotes and backslash escapes are honoured
+///
+/// # Returns
+///
+/// A `Result` containing the `Description` or a `DescribeError`.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::grammar::describe::describe_command;
+/// let description = describe_command("ffmpeg -i a.mkv -c:v libx265 -crf 28 -an b.mp4").unwrap();
+/// assert_eq!(description.sentence(), "transcode a.mkv to b.mp4 using h265");
+/// assert_eq!(description.unsupported, vec!["-crf 28", "-an"]);
+/// ```
+pub fn describe_command(command_line: &str) -> Result<Description, DescribeError> {
+    describe_args(&split_command_line(command_line)?)
+}
+
+/// Reads an ffmpeg command given as separate arguments, starting with the program.
+///
+/// The operation is chosen from the options that give it away, such as `-vn`
+/// for an audio extraction or a `scale` filter for a resize. Options the
+/// chosen intent cannot express, extra inputs and extra outputs are listed
+/// in `Description::unsupported`; options that only affect ffmpeg's console
+/// output are dropped.
+pub fn describe_args<S: AsRef<str>>(args: &[S]) -> Result<Description, DescribeError> {
+    let program = args.first().map_or("", AsRef::as_ref);
+    if Path::new(program).file_stem().is_none_or(|stem| stem != "ffmpeg") {
+        return Err(DescribeError::NotFfmpeg(program.to_string()));
+    }
+
+    let mut options = Options::default();
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let mut rest = args[1..].iter().map(AsRef::as_ref);
+    while let Some(arg) = rest.next() {
+        if arg.len() < 2 || !arg.starts_with('-') {
+            outputs.push(arg.to_string());
+            continue;
+        }
+
+        let value = match FLAGS.contains(&arg) {
+            true => None,
+            false => Some(rest.next().ok_or_else(|| DescribeError::MissingValue(arg.to_string()))?.to_string()),
+        };
+        match (arg, value) {
+            ("-i", Some(input)) => inputs.push(PathBuf::from(input)),
+            (name, value) => options.0.push((name.to_string(), value)),
+        }
+    }
+
+    let output_path = outputs.pop().map(PathBuf::from).ok_or(DescribeError::MissingOutput)?;
+    if inputs.is_empty() {
+        return Err(DescribeError::MissingInput);
+    }
+
+    let mut intent = read_intent(inputs, output_path, &mut options);
+
+    let mut unsupported = Vec::new();
+    if !matches!(intent.operation, Operation::Concat(_)) {
+        for input in intent.input_paths.drain(1..) {
+            unsupported.push(format!("-i {}", input.display()));
+        }
+    }
+    unsupported.extend(outputs);
+    for (name, value) in options.0 {
+        if QUIET.contains(&name.as_str()) {
+            continue;
+        }
+        match value {
+            Some(value) => unsupported.push(format!("{} {}", name, value)),
+            None => unsupported.push(name),
+        }
+    }
+
+    Ok(Description { intent, unsupported })
+}
+
+/// Renders an intent as a sentence that `Parser` reads back into the same intent.
+///
+/// Every output is named explicitly, so the sentence does not depend on how
+/// default output names are derived. The steps of a chain are joined with
+/// `then`, each later step working on `it`.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::{Tokenizer, Parser};
+/// use ffrs::grammar::describe::sentence;
+/// let intent = Parser::new(Tokenizer::new("resize clip.mp4 to 720p").tokenize()).parse().unwrap();
+/// assert_eq!(sentence(&intent), "resize clip.mp4 to 720 tall as clip_resized.mp4");
+/// ```
+pub fn sentence(intent: &Intent) -> String {
+    let input = intent.input_path().map(quote_path).unwrap_or_default();
+    let output = quote_path(&intent.output_path);
+
+    clause(intent, &intent.operation, &input, Some(&output))
+}
+
+/// Renders one operation as a clause reading `input`, naming `output` when given.
+///
+/// Steps in the middle of a chain have no output of their own; their result
+/// goes straight on to the next step.
+fn clause(intent: &Intent, operation: &Operation, input: &str, output: Option<&str>) -> String {
+    let timestamp = |seconds: f64| time::format_timestamp(seconds);
+    let named = |text: String, word: &str| match output {
+        Some(output) => format!("{} {} {}", text, word, output),
+        None => text,
+    };
+
+    match operation {
+        Operation::Convert => named(format!("convert {}", input), "to"),
+        Operation::Resize(resolution) => named(format!("resize {} to {}", input, resolution), "as"),
+        Operation::Transcode { video, audio } => {
+            let mut text = named(format!("transcode {}", input), "to");
+            let names: Vec<&str> = [video, audio].into_iter().flatten().map(|codec| codec.name).collect();
+            if !names.is_empty() {
+                text.push_str(&format!(" using {}", names.join(" and ")));
+            }
+            text
+        },
+        Operation::ExtractAudio(quality) => {
+            let mut text = named(format!("extract audio from {}", input), "as");
+            match quality {
+                Quality::Default => {},
+                Quality::Bitrate(bitrate) => text.push_str(&format!(" at {}", bitrate)),
+                Quality::Copy { .. } => text.push_str(" without re-encoding"),
+            }
+            text
+        },
+        Operation::Trim { range, mode } => {
+            let mut text = match range.span() {
+                TimeSpan::Last(length) => format!("keep the last {} of {}", timestamp(length), input),
+                _ => format!("trim {}", input),
+            };
+            if let Some(start) = range.start() {
+                text.push_str(&format!(" from {}", timestamp(start)));
+            }
+            match range.span() {
+                TimeSpan::Between { end: Some(end), .. } => text.push_str(&format!(" to {}", timestamp(end))),
+                TimeSpan::For { duration, .. } => text.push_str(&format!(" for {}", timestamp(duration))),
+                _ => {},
+            }
+            if *mode == TrimMode::Accurate {
+                text.push_str(" precisely");
+            }
+            named(text, "as")
+        },
+        Operation::Concat(_) => {
+            let inputs: Vec<String> = intent.input_paths.iter().map(|path| quote_path(path)).collect();
+            named(format!("join {}", inputs.join(" and ")), "into")
+        },
+        Operation::Gif(options) => {
+            let defaults = GifOptions::default();
+            let mut text = format!("make a gif from {}", input);
+            match options.range.map(|range| range.span()) {
+                Some(TimeSpan::Between { start, end }) => {
+                    if let Some(start) = start {
+                        text.push_str(&format!(" from {}", timestamp(start)));
+                    }
+                    if let Some(end) = end {
+                        text.push_str(&format!(" to {}", timestamp(end)));
+                    }
+                },
+                Some(TimeSpan::For { start, duration }) => {
+                    if let Some(start) = start {
+                        text.push_str(&format!(" from {}", timestamp(start)));
+                    }
+                    text.push_str(&format!(" for {}", timestamp(duration)));
+                },
+                Some(TimeSpan::Last(length)) => text.push_str(&format!(" for the last {}", timestamp(length))),
+                None => {},
+            }
+            if options.fps != defaults.fps {
+                text.push_str(&format!(" at {} fps", options.fps.filter_value()));
+            }
+            if options.width != defaults.width {
+                text.push_str(&format!(" {} wide", options.width));
+            }
+            named(text, "as")
+        },
+        Operation::Thumbnail { time: Some(time) } => {
+            named(format!("grab a frame from {} at {}", input, timestamp(time.seconds())), "as")
+        },
+        Operation::Thumbnail { time: None } => named(format!("grab a thumbnail of {}", input), "as"),
+        Operation::ExtractFrames(fps) => named(format!("extract {} from {}", fps, input), "to"),
+        Operation::Inspect => format!("inspect {}", input),
+        Operation::Pipeline(steps) => steps.iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let input = if index == 0 { input } else { "it" };
+                let output = output.filter(|_| index + 1 == steps.len());
+                clause(intent, step, input, output)
+            })
+            .collect::<Vec<_>>()
+            .join(", then "),
+    }
+}
+
+/// The options of a command that are not yet accounted for, in order.
+#[derive(Debug, Default)]
+struct Options(Vec<(String, Option<String>)>);
+
+impl Options {
+    /// Removes the first of the named options and returns its value, or an empty string for a flag.
+    fn take(&mut self, names: &[&str]) -> Option<String> {
+        self.take_if(names, |_| true)
+    }
+
+    /// Removes the first of the named options whose value passes `accept`.
+    fn take_if(&mut self, names: &[&str], accept: impl Fn(&str) -> bool) -> Option<String> {
+        let index = self.0.iter()
+            .position(|(name, value)| names.contains(&name.as_str()) && accept(value.as_deref().unwrap_or_default()))?;
+        Some(self.0.remove(index).1.unwrap_or_default())
+    }
+
+    /// Removes the first of the named options holding a timestamp and returns it in seconds.
+    fn take_seconds(&mut self, names: &[&str]) -> Option<f64> {
+        self.take_if(names, |value| time::parse_timestamp(value).is_some())
+            .and_then(|value| time::parse_timestamp(&value))
+    }
+}
+
+/// Works out the intent behind the options, removing each option it accounts for.
+fn read_intent(input_paths: Vec<PathBuf>, output_path: PathBuf, options: &mut Options) -> Intent {
+    let extension = output_path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    let output_name = output_path.to_string_lossy().into_owned();
+    let count = input_paths.len();
+
+    let concat_streams = |graph: &str| {
+        [(true, true), (true, false), (false, true)].into_iter()
+            .find(|&(video, audio)| count > 1 && graph == concat_filter(count, video, audio).to_string())
+    };
+    let operation = if let Some((video, audio)) = options.take_if(&["-filter_complex"], |graph| concat_streams(graph).is_some())
+        .and_then(|graph| concat_streams(&graph))
+    {
+        if video {
+            options.take_if(&["-map"], |map| map == "[v]");
+        }
+        if audio {
+            options.take_if(&["-map"], |map| map == "[a]");
+        }
+        Operation::Concat(ConcatMethod::Filter { video, audio })
+    } else if options.take(&["-vn"]).is_some() {
+        Operation::ExtractAudio(read_audio_options(options, &extension))
+    } else if let Some((fps, width)) = options.take_if(VIDEO_FILTER, |filter| read_gif_filter(filter).is_some())
+        .and_then(|filter| read_gif_filter(&filter))
+    {
+        let start = options.take_seconds(&["-ss"]);
+        let end = options.take_seconds(&["-to"]);
+        let duration = options.take_seconds(&["-t"]);
+        let range = match (start, end, duration) {
+            (start, None, Some(duration)) => TimeRange::starting_for(start, duration).ok(),
+            (start, end, _) => TimeRange::between(start, end).ok(),
+        };
+        options.take_if(&["-loop"], |count| count == "0");
+        Operation::Gif(GifOptions { range, fps, width })
+    } else if options.take_if(FRAMES, |count| count == "1").is_some() {
+        let time = options.take_seconds(&["-ss"]).and_then(|seconds| TimePoint::new(seconds).ok());
+        if time.is_none() {
+            options.take_if(VIDEO_FILTER, |filter| filter == "thumbnail");
+        }
+        Operation::Thumbnail { time }
+    } else if let Some(fps) = options.take_if(VIDEO_FILTER, |filter| {
+        output_name.contains('%') && read_frame_rate(filter).is_some()
+    }).and_then(|filter| read_frame_rate(&filter))
+    {
+        Operation::ExtractFrames(fps)
+    } else if let Some(range) = read_time_range(options) {
+        let mode = match options.take_if(STREAM_COPY, |codec| codec == "copy") {
+            Some(_) => TrimMode::Copy,
+            None => TrimMode::Accurate,
+        };
+        Operation::Trim { range, mode }
+    } else if let Some(resolution) = options.take_if(VIDEO_FILTER, |filter| read_scale_filter(filter).is_some())
+        .and_then(|filter| read_scale_filter(&filter))
+    {
+        Operation::Resize(resolution)
+    } else {
+        let mut take_codec = |names: &[&str], kind: CodecKind| {
+            let known = |name: &str| codecs::lookup_codec(name).filter(|codec| codec.kind == kind);
+            options.take_if(names, |name| known(name).is_some()).and_then(|name| known(&name))
+        };
+        let video = take_codec(VIDEO_CODEC, CodecKind::Video);
+        let audio = take_codec(AUDIO_CODEC, CodecKind::Audio);
+        if video.is_none() && audio.is_none() {
+            Operation::Convert
+        } else {
+            Operation::Transcode { video, audio }
+        }
+    };
+
+    Intent {
+        operation,
+        input_paths,
+        output_path,
+        sources: HashMap::new(),
+    }
+}
+
+/// Accounts for the encoder, quality and stream copy options of an audio extraction.
+///
+/// Only the encoder and quality the builder would pick for the output
+/// extension are taken, along with any valid bitrate for a lossy format.
+fn read_audio_options(options: &mut Options, extension: &str) -> Quality {
+    if options.take_if(AUDIO_CODEC, |codec| codec == "copy").is_some() {
+        return Quality::Copy { source: None };
+    }
+
+    let Some((encoder, default_quality)) = audio_encoding(extension) else {
+        return Quality::Default;
+    };
+    options.take_if(AUDIO_CODEC, |codec| codec == encoder);
+
+    if let Some((flag, value)) = default_quality {
+        if options.take_if(&[flag], |quality| quality == value).is_none() {
+            if let Some(bitrate) = options.take_if(AUDIO_BITRATE, |bitrate| read_bitrate(bitrate).is_some())
+                .and_then(|bitrate| read_bitrate(&bitrate))
+            {
+                return Quality::Bitrate(bitrate);
+            }
+        }
+    }
+    Quality::Default
+}
+
+/// Accounts for the seek and length options of a trim, if there are any.
+fn read_time_range(options: &mut Options) -> Option<TimeRange> {
+    let from_end = options.take_if(&["-sseof"], |value| {
+        value.strip_prefix('-').is_some_and(|length| time::parse_timestamp(length).is_some())
+    });
+    if let Some(from_end) = from_end.as_deref().and_then(|value| time::parse_timestamp(&value[1..])) {
+        return TimeRange::last(from_end).ok();
+    }
+
+    let start = options.take_seconds(&["-ss"]);
+    match options.take_seconds(&["-to"]) {
+        Some(end) => TimeRange::between(start, Some(end)).ok(),
+        None => match options.take_seconds(&["-t"]) {
+            Some(duration) => TimeRange::starting_for(start, duration).ok(),
+            None => TimeRange::between(start, None).ok(),
+        },
+    }
+}
+
+/// Reads the frame rate and width back out of the builder's GIF filtergraph.
+fn read_gif_filter(filter: &str) -> Option<(FrameRate, Pixels)> {
+    let (fps, rest) = filter.strip_prefix("fps=")?.split_once(",scale=")?;
+    let (width, _) = rest.split_once(':')?;
+    let fps = parse_frame_rate(fps).ok()?;
+    let width = parse_dimension(width).ok()?;
+    (filter == gif_filter(&fps, width).to_string()).then_some((fps, width))
+}
+
+/// Reads the width and height of a `scale` filter in a form a resize can express.
+fn read_scale_filter(filter: &str) -> Option<Resolution> {
+    let (width, height) = filter.strip_prefix("scale=")?.split_once(':')?;
+    let dimension = |side: &str| parse_dimension(side).ok();
+
+    match (width, height) {
+        ("iw/2", "-2") => Some(Resolution::Half),
+        ("iw/4", "-2") => Some(Resolution::Quarter),
+        ("iw*2", "-2") => Some(Resolution::Double),
+        (_, "-2") => Some(Resolution::Width(dimension(width)?)),
+        ("-2", _) => Some(Resolution::Height(dimension(height)?)),
+        _ => Some(Resolution::Exact { width: dimension(width)?, height: dimension(height)? }),
+    }
+}
+
+/// Reads an `fps` filter for a frame extraction: `fps=2` or one frame every so many seconds, `fps=1/10`.
+fn read_frame_rate(filter: &str) -> Option<FrameRate> {
+    let rate = filter.strip_prefix("fps=")?;
+    match rate.split_once('/') {
+        Some(("1", seconds)) => FrameRate::every(seconds.parse::<f64>().ok()?).ok(),
+        Some(_) => None,
+        None => parse_frame_rate(rate).ok(),
+    }
+}
+
+/// Reads an audio bitrate in kilobits such as `192k`, within the range the grammar accepts.
+fn read_bitrate(bitrate: &str) -> Option<Bitrate> {
+    Bitrate::from_kbps(bitrate.strip_suffix('k')?.parse::<u32>().ok()?).ok()
+}
+
+/// Writes a path so the tokenizer reads it back as one path, quoting it when needed.
+fn quote_path(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    let plain = text.starts_with(|ch: char| ch.is_alphabetic() || ch == '_' || ch == '/')
+        && !text.ends_with('.')
+        && text.chars().all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '-' | '/' | '~' | '%' | '.'));
+
+    if plain {
+        text.into_owned()
+    } else {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Splits a shell command line into arguments.
+///
+/// Honours single quotes, double quotes (in which a backslash escapes the
+/// next character), backslash escapes and backslash-newline continuations.
+fn split_command_line(line: &str) -> Result<Vec<String>, DescribeError> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => arg.push(ch),
+                        None => return Err(DescribeError::UnterminatedQuote),
+                    }
+                }
+            },
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => arg.extend(chars.next()),
+                        Some(ch) => arg.push(ch),
+                        None => return Err(DescribeError::UnterminatedQuote),
+                    }
+                }
+            },
+            '\\' => match chars.next() {
+                Some('\n') | None => {},
+                Some(ch) => current.get_or_insert_with(String::new).push(ch),
+            },
+            ch if ch.is_whitespace() => args.extend(current.take()),
+            ch => current.get_or_insert_with(String::new).push(ch),
+        }
+    }
+    args.extend(current);
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_builder::CommandBuilder;
+    use crate::grammar::{Parser, Tokenizer};
+
+    fn parse(command: &str) -> Intent {
+        let mut tokenizer = Tokenizer::new(command);
+        Parser::new(tokenizer.tokenize()).parse().unwrap()
+    }
+
+    #[test]
+    fn test_describe_flags_what_it_cannot_express() {
+        let description = describe_command("ffmpeg -y -hide_banner -i a.mkv -c:v libx265 -crf 28 -an b.mp4").unwrap();
+
+        assert_eq!(description.intent.operation, Operation::Transcode {
+            video: codecs::lookup_codec("h265"),
+            audio: None,
+        });
+        assert_eq!(description.sentence(), "transcode a.mkv to b.mp4 using h265");
+        assert_eq!(description.unsupported, vec!["-y", "-crf 28", "-an"]);
+
+        let description = describe_command("ffmpeg -i a.mp4 -i logo.png -vf scale=1280:-1 'My Clip.mp4'").unwrap();
+        assert_eq!(description.sentence(), "convert a.mp4 to \"My Clip.mp4\"");
+        assert_eq!(description.unsupported, vec!["-i logo.png", "-vf scale=1280:-1"]);
+    }
+
+    #[test]
+    fn test_describe_rejects_malformed_commands() {
+        assert!(matches!(describe_command("sox a.wav b.mp3"), Err(DescribeError::NotFfmpeg(_))));
+        assert!(matches!(describe_command("ffmpeg -i 'a.mp4 b.mp4"), Err(DescribeError::UnterminatedQuote)));
+        assert!(matches!(describe_command("ffmpeg -i a.mp4 -ss"), Err(DescribeError::MissingValue(_))));
+        assert!(matches!(describe_command("ffmpeg -i a.mp4"), Err(DescribeError::MissingOutput)));
+        assert!(matches!(describe_command("ffmpeg b.mp4"), Err(DescribeError::MissingInput)));
+    }
+
+    #[test]
+    fn test_pipeline_sentence_reads_back() {
+        for command in [
+            "trim interview.mp4 from 0:10 to 5:00, resize it to 720p and convert to webm",
+            "resize clip.mp4 to half size, then transcode it using vp9 and then grab a thumbnail of it",
+            "trim talk.mp4 from 1:00 precisely and then extract the audio at 96 kbps",
+        ] {
+            let intent = parse(command);
+            assert!(matches!(intent.operation, Operation::Pipeline(_)), "{}", command);
+
+            let sentence = sentence(&intent);
+            let mut reparsed = parse(&sentence);
+            reparsed.sources = intent.sources.clone();
+            assert_eq!(reparsed, intent, "{}", sentence);
+        }
+
+        let intent = parse("trim interview.mp4 from 0:10 to 5:00 and then resize it to 720p as short.mp4");
+        assert_eq!(
+            sentence(&intent),
+            "trim interview.mp4 from 00:00:10 to 00:05:00, then resize it to 720 tall as short.mp4"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_through_builder_and_parser() {
+        let commands = [
+            "convert video.mp4 to video.avi",
+            "convert 'My Holiday.mov' to .mp4",
+            "resize clip.mp4 to 1280x720",
+            "resize clip.mp4 to 720p",
+            "resize clip.mp4 to half size",
+            "transcode a.mkv to b.mp4 using h265 and opus",
+            "extract audio from talk.mp4",
+            "extract audio from talk.mp4 as m4a at 96 kbps",
+            "extract audio from talk.mp4 without re-encoding",
+            "extract audio from talk.mp4 as lossless",
+            "trim talk.mp4 from 1:30 to 2:45",
+            "trim talk.mp4 from 10 for 30 seconds precisely as part.mp4",
+            "keep the last 20 seconds of talk.mp4",
+            "join a.mp4, b.mp4 and c.mp4 into all.mp4",
+            "make a gif from clip.mp4 from 0:05 to 0:08 at 15 fps 320 wide",
+            "grab a frame from clip.mp4 at 1:00 as poster.png",
+            "make a thumbnail of clip.mp4",
+            "extract one frame every 10 seconds from clip.mp4 into shots/",
+            "extract 2 frames per second from clip.mp4 as png",
+        ];
+
+        for command in commands {
+            let mut intent = parse(command);
+            intent.sources.clear();
+
+            let cmd = CommandBuilder::new().build_command(&intent).unwrap();
+            let mut args = vec!["ffmpeg".to_string()];
+            args.extend(cmd.get_args().iter().map(|arg| arg.to_string_lossy().into_owned()));
+
+            let description = describe_args(&args).unwrap();
+            assert_eq!(description.intent, intent, "{}", command);
+            assert!(description.unsupported.is_empty(), "{}: {:?}", command, description.unsupported);
+
+            let mut reparsed = parse(&description.sentence());
+            reparsed.sources.clear();
+            assert_eq!(reparsed, intent, "{}", description.sentence());
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use crate::grammar::parser::ParseError;
+use crate::intent::types::OperationType;
+use std::fmt;
+use std::ops::Range;
+
+/// A parse error together with where it happened and how to fix it.
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// What went wrong
+    pub error: ParseError,
+    /// The byte range of the offending words in the command, if known
+    pub span: Option<Range<usize>>,
+    /// The known word or file name closest to the offending one
+    pub suggestion: Option<String>,
+    /// A command showing how the operation being parsed is written
+    pub example: &'static str,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean '{}'?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl Diagnostic {
+    /// Renders the command with a caret line under the offending words,
+    /// followed by the suggestion and the example.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command text the spans refer to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ffrs::grammar::diagnostic::Diagnostic;
+    /// use ffrs::grammar::parser::ParseError;
+    /// let diagnostic = Diagnostic {
+    ///     error: ParseError::UnexpectedToken("Unknown operation: covert".to_string()),
+    ///     span: Some(0..6),
+    ///     suggestion: Some("convert".to_string()),
+    ///     example: "convert video.mp4 to video.avi",
+    /// };
+    /// assert_eq!(
+    ///     diagnostic.render("covert a.mp4 to b.avi"),
+    ///     "  covert a.mp4 to b.avi\n  ^^^^^^\nDid you mean 'convert'?\nExample: 'convert video.mp4 to video.avi'\n"
+    /// );
+    /// ```
+    pub fn render(&self, command: &str) -> String {
+        let mut text = String::new();
+
+        if let Some(span) = self.span.clone().filter(|span| span.start <= command.len()) {
+            let end = span.end.min(command.len());
+            let before = &command[..span.start];
+            let underlined = &command[span.start..end];
+            text.push_str(&format!("  {}\n", command));
+            text.push_str(&format!(
+                "  {}{}\n",
+                " ".repeat(display_width(before)),
+                "^".repeat(display_width(underlined).max(1))
+            ));
+        }
+        if let Some(suggestion) = &self.suggestion {
+            text.push_str(&format!("Did you mean '{}'?\n", suggestion));
+        }
+        text.push_str(&format!("Example: '{}'\n", self.example));
+
+        text
+    }
+}
+
+/// Returns a command showing how an operation is written, or a plain
+/// conversion when the operation is not known yet.
+pub fn example(kind: Option<&OperationType>) -> &'static str {
+    match kind {
+        None | Some(OperationType::Convert) => "convert video.mp4 to video.avi",
+        Some(OperationType::Resize) => "resize clip.mp4 to 720p",
+        Some(OperationType::Transcode) => "transcode movie.mkv to movie.mp4 using h265 and opus",
+        Some(OperationType::ExtractAudio) => "extract audio from talk.mp4 as mp3",
+        Some(OperationType::Trim) => "trim talk.mp4 from 1:30 to 2:45",
+        Some(OperationType::Concat) => "join part1.mp4, part2.mp4 and part3.mp4 into full.mp4",
+        Some(OperationType::Gif) => "make a gif from clip.mp4 from 0:05 for 3 seconds",
+        Some(OperationType::Thumbnail) => "grab a frame from clip.mp4 at 1:00",
+        Some(OperationType::ExtractFrames) => "extract one frame every 10 seconds from clip.mp4 into shots/",
+        Some(OperationType::Inspect) => "inspect clip.mp4",
+        Some(OperationType::Pipeline) => "trim interview.mp4 from 0:10 to 5:00, then resize it to 720p",
+    }
+}
+
+/// Returns the candidate closest to `word` by edit distance, if it is close
+/// enough to be a likely typo: one edit for short words, and one per three
+/// characters for longer ones.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::grammar::diagnostic::closest_word;
+/// assert_eq!(closest_word("covert", &["convert", "resize"]), Some("convert"));
+/// assert_eq!(closest_word("banana", &["convert", "resize"]), None);
+/// ```
+pub fn closest_word<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    let allowed = (word.chars().count() / 3).max(1);
+
+    candidates.iter()
+        .filter(|candidate| **candidate != word)
+        .map(|candidate| (edit_distance(&word, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= allowed)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Counts the insertions, deletions, substitutions and swaps of neighbouring
+/// characters that turn `a` into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // rows[i][j] is the distance between the first i chars of a and the first j of b.
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Returns how many terminal columns the text takes up, so the caret lines
+/// up under accented, wide and combined characters.
+fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("covert", "convert"), 1);
+        assert_eq!(edit_distance("ot", "to"), 1);
+        assert_eq!(edit_distance("mpp4", "mp4"), 1);
+        assert_eq!(edit_distance("resize", "resize"), 0);
+        assert_eq!(edit_distance("", "gif"), 3);
+    }
+
+    #[test]
+    fn test_render_aligns_caret_under_wide_characters() {
+        let diagnostic = Diagnostic {
+            error: ParseError::UnsupportedFormat("影片.mpp4".to_string()),
+            span: Some(19..30),
+            suggestion: Some("影片.mp4".to_string()),
+            example: example(Some(&OperationType::Convert)),
+        };
+
+        let rendered = diagnostic.render("convert vidéo.mp4 影片.mpp4");
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[1], format!("  {}^^^^^^^^^", " ".repeat(18)));
+        assert_eq!(lines[2], "Did you mean '影片.mp4'?");
+    }
+}
@@ -1,10 +1,13 @@
 //! Grammar parsing module for the FF CLI tool.
 //!
 //! This module provides tokenization and parsing functionality to convert
-//! plain English commands into structured intents.
+//! plain English commands into structured intents, and reads existing ffmpeg
+//! commands back into intents and English.
 
 pub mod tokenizer;
 pub mod parser;
+pub mod describe;
+pub mod diagnostic;
 
 pub use tokenizer::*;
 pub use parser::*;
\ No newline at end of file
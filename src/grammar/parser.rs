@@ -1,13 +1,33 @@
-use crate::intent::types::{Intent, OperationType};
-use std::path::PathBuf;
-use crate::grammar::tokenizer::Token;
+use crate::intent::params::{
+    Bitrate, ConcatMethod, FrameRate, GifOptions, ParamError, Pixels, Quality, Resolution, TimePoint, TimeRange,
+    TrimMode,
+};
+use crate::intent::types::{Intent, Operation, OperationType};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use crate::grammar::diagnostic::{self, Diagnostic};
+use crate::grammar::tokenizer::{Token, Tokenizer};
+use crate::utils::codecs::{self, Codec, CodecKind};
 use crate::utils::file_utils;
+use crate::utils::time;
 
 /// Parser for converting tokens into structured intents.
 #[derive(Debug)]
 pub struct Parser {
     tokens: Vec<Token>,
+    /// The byte range of each token in the command, when known
+    spans: Vec<Range<usize>>,
+    /// The command the tokens were read from, when known
+    command: Option<String>,
     position: usize,
+    sources: HashMap<String, String>,
+    /// The output of the previous step of a chain, which `it` refers to
+    previous_output: Option<String>,
+    /// The output file the current step named explicitly, if any
+    named_output: Option<String>,
+    /// The operation being parsed, which chooses the example shown with an error
+    operation: Option<OperationType>,
 }
 
 /// Error types that can occur during parsing.
@@ -21,6 +41,12 @@ pub enum ParseError {
     InvalidPath(String),
     /// An unsupported format was specified
     UnsupportedFormat(String),
+    /// A parameter value was out of range or malformed
+    InvalidParameter(String),
+    /// A codec name that is not in the codec table
+    UnknownCodec(String),
+    /// A batch pattern that matched no media files
+    NoMatchingFiles(String),
 }
 
 impl std::fmt::Display for ParseError {
@@ -30,12 +56,26 @@ impl std::fmt::Display for ParseError {
             ParseError::MissingToken(expected) => write!(f, "Missing expected token: {}", expected),
             ParseError::InvalidPath(path) => write!(f, "Invalid path: {}", path),
             ParseError::UnsupportedFormat(format) => write!(f, "Unsupported format: {}", format),
+            ParseError::InvalidParameter(message) => write!(f, "Invalid parameter: {}", message),
+            ParseError::UnknownCodec(name) => write!(
+                f,
+                "Unknown codec: {} (known codecs: {})",
+                name,
+                codecs::known_codec_names().join(", ")
+            ),
+            ParseError::NoMatchingFiles(pattern) => write!(f, "No media files match: {}", pattern),
         }
     }
 }
 
 impl std::error::Error for ParseError {}
 
+impl From<ParamError> for ParseError {
+    fn from(error: ParamError) -> Self {
+        ParseError::InvalidParameter(error.to_string())
+    }
+}
+
 impl Parser {
     /// Creates a new parser with the given tokens.
     ///
@@ -43,26 +83,227 @@ impl Parser {
     ///
     /// * `tokens` - A vector of tokens to parse
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, position: 0 }
+        Self {
+            tokens,
+            spans: Vec::new(),
+            command: None,
+            position: 0,
+            sources: HashMap::new(),
+            previous_output: None,
+            named_output: None,
+            operation: None,
+        }
+    }
+
+    /// Creates a parser for tokens that carry the byte ranges they were read
+    /// from, so that `diagnose` can point at the words behind an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Tokens with their spans, as from `Tokenizer::tokenize_with_spans`
+    pub fn with_spans(tokens: Vec<(Token, Range<usize>)>) -> Self {
+        let (tokens, spans) = tokens.into_iter().unzip();
+        Self { spans, ..Self::new(tokens) }
+    }
+
+    /// Creates a parser for a command, keeping its text so that the words
+    /// recorded as each setting's source are exactly the ones written.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to tokenize and parse
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ffrs::Parser;
+    /// let intent = Parser::for_command("trim talk.mp4 from 1:30").parse().unwrap();
+    /// assert_eq!(intent.source("start"), Some("from 1:30"));
+    /// ```
+    pub fn for_command(command: &str) -> Self {
+        let tokens = Tokenizer::new(command).tokenize_with_spans();
+        Self { command: Some(command.to_string()), ..Self::with_spans(tokens) }
+    }
+
+    /// Explains an error returned by this parser.
+    ///
+    /// The offending token is the one a file or codec error names, or else
+    /// the one parsing stopped at. A word close to a known keyword, codec or
+    /// format gets a suggestion, and the example shows the operation being
+    /// parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ffrs::{Parser, Tokenizer};
+    /// let mut parser = Parser::with_spans(Tokenizer::new("covert a.mp4 to b.avi").tokenize_with_spans());
+    /// let error = parser.parse().unwrap_err();
+    /// let diagnostic = parser.diagnose(error);
+    /// assert_eq!(diagnostic.span, Some(0..6));
+    /// assert_eq!(diagnostic.suggestion.as_deref(), Some("convert"));
+    /// ```
+    pub fn diagnose(&self, error: ParseError) -> Diagnostic {
+        let named = match &error {
+            ParseError::UnsupportedFormat(name) | ParseError::UnknownCodec(name) | ParseError::InvalidPath(name) => {
+                self.tokens.iter().position(|token| {
+                    token_text(token).is_some_and(|text| {
+                        text.eq_ignore_ascii_case(name) || (text.starts_with('.') && name.ends_with(text))
+                    })
+                })
+            },
+            _ => None,
+        };
+        let index = named.unwrap_or(self.position.min(self.tokens.len()));
+
+        let span = match self.spans.get(index) {
+            Some(span) => Some(span.clone()),
+            None => self.spans.last().map(|last| last.end..last.end + 1),
+        };
+        let suggestion = self.tokens.get(index)
+            .and_then(|token| token_text(token).map(|text| (token, text)))
+            .and_then(|(token, text)| suggest(&error, token, text));
+
+        Diagnostic { example: diagnostic::example(self.operation.as_ref()), error, span, suggestion }
     }
 
     /// Parses the tokens into an Intent struct.
     ///
+    /// Steps joined by `then`, `and then`, `and` or a comma form a chain,
+    /// as in `trim a.mp4 from 0:10 to 5:00, resize it to 720p and convert
+    /// to webm`. Each later step works on the result of the one before,
+    /// named `it` or `the result`, or not named at all. The chain reads the
+    /// first step's input and writes the last step's output; a `convert`
+    /// step only chooses that output. Only the last step may name an output
+    /// file, since nothing is written in between. Words left over after the
+    /// last clause are an error, so a misspelled option is reported rather
+    /// than ignored.
+    ///
     /// # Returns
     ///
     /// A `Result` containing either the parsed `Intent` or a `ParseError`.
     pub fn parse(&mut self) -> Result<Intent, ParseError> {
-        let operation = self.parse_operation()?;
+        self.accept_word(FILLERS);
+        let mut verb = self.peek_word().unwrap_or_default().to_string();
+        let first = self.parse_step()?;
+        let input_paths = first.input_paths;
+        let mut output_path = first.output_path;
+        let mut steps = vec![first.operation];
 
-        let input_path = self.parse_path()?;
+        while self.accept_step_break() {
+            if let Some(named) = self.named_output.take() {
+                self.operation = Some(OperationType::Pipeline);
+                return Err(ParseError::InvalidParameter(format!(
+                    "only the last step of a chain can name its output; {} would never be written",
+                    named
+                )));
+            }
+            self.previous_output = Some(output_path.to_string_lossy().into_owned());
 
-        if !file_utils::is_supported_format(&input_path) {
-            return Err(ParseError::UnsupportedFormat(input_path.clone()));
+            let next_verb = self.peek_word().unwrap_or_default().to_string();
+            let step = self.parse_step()?;
+            for (verb, operation) in [(&verb, &steps[0]), (&next_verb, &step.operation)] {
+                if matches!(operation, Operation::Concat(_) | Operation::Inspect) {
+                    self.operation = Some(OperationType::Pipeline);
+                    return Err(ParseError::InvalidParameter(format!(
+                        "'{}' cannot be chained with other steps; run it on its own",
+                        verb
+                    )));
+                }
+            }
+            steps.push(step.operation);
+            output_path = step.output_path;
+            verb = next_verb;
         }
 
-        self.expect_word("to")?;
+        if self.peek().is_some() {
+            // A misspelled verb leaves the words joining its step behind; point past them at the verb.
+            let start = self.position;
+            if matches!(self.peek(), Some(Token::Comma)) {
+                self.position += 1;
+            }
+            self.accept_word(&["and"]);
+            self.accept_word(&["then", "afterwards"]);
+            self.accept_word(FILLERS);
+            return Err(ParseError::UnexpectedToken(match self.peek() {
+                Some(token) if self.position > start => format!("Unknown operation: {}", token),
+                _ => {
+                    self.position = start;
+                    format!("nothing more was expected, got: {}", self.tokens[start])
+                },
+            }));
+        }
+
+        steps.retain(|step| *step != Operation::Convert);
+        let operation = match steps.len() {
+            0 => Operation::Convert,
+            1 => steps.remove(0),
+            _ => Operation::Pipeline(steps),
+        };
+
+        Ok(Intent {
+            operation,
+            input_paths,
+            output_path,
+            sources: std::mem::take(&mut self.sources),
+        })
+    }
+
+    /// Parses a single step of a command, starting at its verb.
+    fn parse_step(&mut self) -> Result<Intent, ParseError> {
+        self.named_output = None;
+        let verb = self.peek_word().unwrap_or_default().to_string();
+        let operation_start = self.position;
+        let kind = self.parse_operation()?;
+        self.note_source("operation", operation_start);
+        self.operation = Some(kind.clone());
+
+        let intent = match kind {
+            OperationType::Trim => self.parse_trim(&verb)?,
+            OperationType::Concat => self.parse_concat()?,
+            OperationType::Gif => self.parse_gif()?,
+            OperationType::Thumbnail => self.parse_thumbnail()?,
+            OperationType::ExtractFrames => self.parse_frames()?,
+            OperationType::ExtractAudio => self.parse_extract_audio()?,
+            OperationType::Inspect => self.parse_inspect()?,
+            _ => self.parse_conversion(kind)?,
+        };
+
+        Ok(intent)
+    }
 
-        let output_path = self.parse_output_path_or_format(&input_path)?;
+    /// Parses the rest of a convert, resize or transcode: `<file> to <output>`,
+    /// where `into` or `as` may stand for `to`, a resize may give a size
+    /// instead of an output and a transcode may go straight to its codecs
+    /// (`transcode it using h265`).
+    fn parse_conversion(&mut self, kind: OperationType) -> Result<Intent, ParseError> {
+        let input_path = self.parse_input_path()?;
+
+        let names_target = kind != OperationType::Transcode || self.peek_word().is_some_and(|word| TARGETS.contains(&word));
+        if names_target && self.accept_word(TARGETS).is_none() {
+            self.expect_word("to")?;
+        }
+
+        let (operation, output_path) = match kind {
+            OperationType::Resize if !self.next_is_output() => {
+                let start = self.position - 1;
+                let resolution = self.parse_dimensions()?;
+                self.note_source("size", start);
+                (Operation::Resize(resolution), self.parse_optional_output(&input_path, "resized")?)
+            },
+            OperationType::Resize => {
+                (Operation::Resize(Resolution::default()), self.parse_output_path_or_format(&input_path)?)
+            },
+            OperationType::Transcode if names_target => {
+                let output_path = self.parse_output_path_or_format(&input_path)?;
+                let (video, audio) = self.parse_codecs()?;
+                (Operation::Transcode { video, audio }, output_path)
+            },
+            OperationType::Transcode => {
+                let (video, audio) = self.parse_codecs()?;
+                (Operation::Transcode { video, audio }, self.parse_optional_output(&input_path, "transcoded")?)
+            },
+            _ => (Operation::Convert, self.parse_output_path_or_format(&input_path)?),
+        };
 
         if output_path.contains('.') && !file_utils::is_supported_format(&output_path) {
             return Err(ParseError::UnsupportedFormat(output_path.clone()));
@@ -70,142 +311,1936 @@ impl Parser {
 
         Ok(Intent {
             operation,
-            input_path: PathBuf::from(input_path),
+            input_paths: vec![PathBuf::from(input_path)],
             output_path: PathBuf::from(output_path),
-            parameters: std::collections::HashMap::new(),
+            sources: HashMap::new(),
         })
     }
 
-    fn parse_operation(&mut self) -> Result<OperationType, ParseError> {
-        if self.position >= self.tokens.len() {
-            return Err(ParseError::MissingToken("operation".to_string()));
+    /// Parses a command that may name a whole batch of files.
+    ///
+    /// Accepts a glob such as `raw/*.mov`, or `all`/`every`/`each` followed
+    /// by a pattern or format (`*.mov`, `.mov`, `wav`), an optional `files`
+    /// and an optional `in <directory>`. The batch is matched against the
+    /// filesystem and the command is parsed once per file, so every output
+    /// name is derived just as it would be for that file alone. A trailing
+    /// `into <directory>` moves all outputs into that directory. Files that
+    /// would share an output get one named after their own extension. A
+    /// join takes every match as an input of one intent.
+    ///
+    /// # Returns
+    ///
+    /// One `Intent` per matched file, or the single parsed `Intent` when the
+    /// command names no batch.
+    pub fn parse_all(&mut self) -> Result<Vec<Intent>, ParseError> {
+        let Some(source) = self.find_batch_source() else {
+            return self.parse().map(|intent| vec![intent]);
+        };
+
+        // Errors from here on are about the matched files, so point at the pattern.
+        self.position = source.start;
+        let pattern = source.directory.join(&source.pattern).to_string_lossy().into_owned();
+        let mut files = file_utils::find_matching_files(&source.directory, &source.pattern)
+            .map_err(|e| ParseError::InvalidPath(format!("{}: {}", source.directory.display(), e)))?;
+        files.retain(|file| file_utils::is_supported_format(file));
+        if files.is_empty() {
+            return Err(ParseError::NoMatchingFiles(pattern));
         }
 
-        match &self.tokens[self.position] {
-            Token::Word(word) => {
-                self.position += 1;
-                
-                match word.as_str() {
-                    "convert" => Ok(OperationType::Convert),
-                    "resize" => Ok(OperationType::Resize),
-                    "transcode" => Ok(OperationType::Transcode),
-                    "extract" | "extractaudio" => Ok(OperationType::ExtractAudio),
-                    _ => Err(ParseError::UnexpectedToken(format!("Unknown operation: {}", word))),
+        // Each matched file stands where the pattern was written.
+        let with_spans = |range: Range<usize>| -> Vec<(Token, Range<usize>)> {
+            range.map(|index| (self.tokens[index].clone(), self.spans.get(index).cloned().unwrap_or_default())).collect()
+        };
+        let pattern_span = match (self.spans.get(source.start), self.spans.get(source.end - 1)) {
+            (Some(first), Some(last)) => first.start..last.end,
+            _ => 0..0,
+        };
+        let before = with_spans(0..source.start);
+        let mut after = with_spans(source.end..self.tokens.len());
+        let output_directory = take_output_directory(&mut after);
+
+        let inputs: Vec<Vec<(Token, Range<usize>)>> = files.iter()
+            .map(|file| vec![(Token::Path(file.to_string_lossy().into_owned()), pattern_span.clone())])
+            .collect();
+        self.operation = Some(Parser::new(before.iter().map(|(token, _)| token.clone()).collect()).parse_operation()?);
+        let groups = if self.operation == Some(OperationType::Concat) {
+            vec![inputs.join(&(Token::Comma, pattern_span.clone()))]
+        } else {
+            inputs
+        };
+
+        let mut intents = groups.into_iter()
+            .map(|group| {
+                let tokens = [before.as_slice(), &group, &after].concat();
+                let mut intent = Parser { command: self.command.clone(), ..Parser::with_spans(tokens) }.parse()?;
+                if let (Some(directory), Some(name)) = (&output_directory, intent.output_path.file_name()) {
+                    intent.output_path = directory.join(name);
                 }
-            },
-            _ => Err(ParseError::UnexpectedToken("Expected operation word".to_string())),
+                Ok(intent)
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        separate_outputs(&mut intents)?;
+        Ok(intents)
+    }
+
+    /// Finds the tokens that name a batch of files, if any.
+    fn find_batch_source(&self) -> Option<BatchSource> {
+        for start in 1..self.tokens.len() {
+            let (pattern, mut end) = match &self.tokens[start] {
+                Token::Path(path) if path.contains('*') => (path.clone(), start + 1),
+                Token::Word(word) if matches!(word.as_str(), "all" | "every" | "each") => {
+                    let pattern = match self.tokens.get(start + 1) {
+                        Some(Token::Path(path)) if path.contains('*') => path.clone(),
+                        Some(Token::Format(format)) => format!("*{}", format),
+                        Some(Token::Word(word)) if file_utils::is_supported_extension(word) => format!("*.{}", word),
+                        _ => continue,
+                    };
+                    (pattern, start + 2)
+                },
+                _ => continue,
+            };
+
+            if matches!(self.tokens.get(end), Some(Token::Word(word)) if word == "file" || word == "files") {
+                end += 1;
+            }
+
+            let mut directory = PathBuf::new();
+            if matches!(self.tokens.get(end), Some(Token::Word(word)) if word == "in" || word == "from") {
+                match self.tokens.get(end + 1) {
+                    Some(Token::Path(path)) => directory = PathBuf::from(path),
+                    Some(Token::Word(word)) if !file_utils::is_supported_extension(word) => {
+                        directory = PathBuf::from(word)
+                    },
+                    _ => {},
+                }
+                if !directory.as_os_str().is_empty() {
+                    end += 2;
+                }
+            }
+
+            // A pattern such as `raw/*.mov` carries its own directory.
+            let full = directory.join(&pattern);
+            let directory = full.parent().map(Path::to_path_buf).unwrap_or_default();
+            let pattern = full.file_name()?.to_string_lossy().into_owned();
+
+            return Some(BatchSource { start, end, directory, pattern });
         }
+
+        None
     }
 
-    fn parse_path(&mut self) -> Result<String, ParseError> {
-        if self.position >= self.tokens.len() {
-            return Err(ParseError::MissingToken("path".to_string()));
+    /// Parses a path and checks that it names a supported media file.
+    ///
+    /// In a later step of a chain the input is the previous step's result,
+    /// either referred to as `it` or `the result` or left out altogether.
+    fn parse_input_path(&mut self) -> Result<String, ParseError> {
+        if let Some(previous_output) = self.previous_output.clone() {
+            if self.accept_reference() {
+                return Ok(previous_output);
+            }
+            if self.names_input_at(self.position) {
+                return Err(ParseError::InvalidParameter(format!(
+                    "each step of a chain works on the result of the step before; say 'it' instead of {}",
+                    self.peek().map(Token::to_string).unwrap_or_default()
+                )));
+            }
+            return Ok(previous_output);
         }
 
-        match &self.tokens[self.position] {
-            Token::Path(path) => {
+        if self.names_input_at(self.position + 1) {
+            self.accept_word(DETERMINERS);
+        }
+        let start = self.position;
+        let input_path = self.parse_path()?;
+        self.note_source("input", start);
+
+        if !file_utils::is_supported_format(&input_path) {
+            return Err(ParseError::UnsupportedFormat(input_path));
+        }
+
+        Ok(input_path)
+    }
+
+    /// Parses the rest of a trim after its verb.
+    ///
+    /// Accepts `trim <file> from <time> to <time>` (or `for <duration>`),
+    /// `cut the first <duration> of <file>`, and `keep the first|last
+    /// <duration> of <file>`, each with an optional `as <output>` and a mode
+    /// word such as `precisely` (re-encode) or `fast` (stream copy).
+    fn parse_trim(&mut self, verb: &str) -> Result<Intent, ParseError> {
+        let start = self.position;
+        self.accept_word(&["the"]);
+        let (input_path, range) = if let Some(edge) = self.accept_word(&["first", "last"]) {
+            let length = self.expect_time("duration")?;
+            let source_end = self.position;
+            if self.accept_before_input(&["of", "from"]).is_none() && self.previous_output.is_none() {
+                return Err(ParseError::MissingToken("of".to_string()));
+            }
+
+            let (key, range) = match (verb == "keep", edge.as_str()) {
+                (true, "first") => ("duration", TimeRange::starting_for(None, length)?),
+                (true, _) => ("from_end", TimeRange::last(length)?),
+                (false, "first") => ("start", TimeRange::between(Some(length), None)?),
+                (false, _) => {
+                    return Err(ParseError::InvalidParameter(
+                        "cutting off the end needs the clip's length; say 'keep the first <time> of <file>' instead"
+                            .to_string(),
+                    ));
+                },
+            };
+            self.note_source_between(key, start, source_end);
+
+            (self.parse_input_path()?, range)
+        } else {
+            let input_path = self.parse_input_path()?;
+            (input_path, self.parse_time_range()?)
+        };
+
+        let mode = self.parse_trim_mode();
+        let output_path = self.parse_optional_output(&input_path, "trimmed")?;
+        let mode = self.parse_trim_mode().or(mode).unwrap_or_default();
+
+        if !file_utils::is_supported_format(&output_path) {
+            return Err(ParseError::UnsupportedFormat(output_path));
+        }
+
+        Ok(Intent {
+            operation: Operation::Trim { range, mode },
+            input_paths: vec![PathBuf::from(input_path)],
+            output_path: PathBuf::from(output_path),
+            sources: HashMap::new(),
+        })
+    }
+
+    /// Parses the rest of a join: a list of inputs separated by commas and/or
+    /// `and`, followed by an optional `into <output>`.
+    fn parse_concat(&mut self) -> Result<Intent, ParseError> {
+        let mut input_paths = vec![self.parse_input_path()?];
+
+        while !self.at_step_break() {
+            let had_comma = matches!(self.peek(), Some(Token::Comma));
+            if had_comma {
                 self.position += 1;
-                Ok(path.clone())
-            },
-            Token::Word(word) => {
-                if word.contains('.') {
-                    self.position += 1;
-                    Ok(word.clone())
-                } else {
-                    Err(ParseError::UnexpectedToken(format!("Expected path, got: {}", word)))
-                }
-            },
-            _ => Err(ParseError::UnexpectedToken("Expected path".to_string())),
+            }
+            if self.accept_word(&["and"]).is_none() && !had_comma {
+                break;
+            }
+            input_paths.push(self.parse_input_path()?);
+        }
+
+        if input_paths.len() < 2 {
+            return Err(ParseError::MissingToken("second file to join".to_string()));
+        }
+
+        let output_path = if self.accept_word(&["into", "to", "as"]).is_some() {
+            self.parse_output_path_or_format(&input_paths[0])?
+        } else {
+            self.parse_optional_output(&input_paths[0], "joined")?
+        };
+
+        if !file_utils::is_supported_format(&output_path) {
+            return Err(ParseError::UnsupportedFormat(output_path));
         }
+
+        Ok(Intent {
+            operation: Operation::Concat(ConcatMethod::default()),
+            input_paths: input_paths.into_iter().map(PathBuf::from).collect(),
+            output_path: PathBuf::from(output_path),
+            sources: HashMap::new(),
+        })
     }
 
-    fn expect_word(&mut self, expected: &str) -> Result<(), ParseError> {
-        if self.position >= self.tokens.len() {
-            return Err(ParseError::MissingToken(expected.to_string()));
+    /// Parses the rest of a `make a gif` command.
+    ///
+    /// Accepts `from|of <file>` followed, in any order, by `from <time>`,
+    /// `to <time>` or `for <duration>`, `at <n> fps`, `<n> wide` or `width
+    /// <n>`, and `as <output>`.
+    fn parse_gif(&mut self) -> Result<Intent, ParseError> {
+        if self.accept_word(&["out"]).is_some() {
+            self.accept_word(&["of"]);
+        } else {
+            self.accept_before_input(&["from", "of"]);
         }
+        let input_path = self.parse_input_path()?;
 
-        match &self.tokens[self.position] {
-            Token::Word(word) => {
-                if word == expected {
+        let mut options = GifOptions::default();
+        let (mut start, mut end, mut duration) = (None, None, None);
+        let mut output_path = None;
+
+        loop {
+            let clause_start = self.position;
+            let keyword = self.accept_word(&["from", "at", "to", "until", "for", "as", "into", "width"]);
+
+            match (keyword.as_deref(), self.peek().cloned()) {
+                (Some("from") | Some("at"), Some(Token::Time(seconds))) => {
                     self.position += 1;
-                    Ok(())
+                    start = Some(seconds);
+                    self.note_source("start", clause_start);
+                },
+                (Some("to") | Some("until"), Some(Token::Time(seconds) | Token::Number(seconds))) => {
+                    self.position += 1;
+                    end = Some(seconds);
+                    self.note_source("end", clause_start);
+                },
+                (Some("for"), Some(Token::Time(seconds) | Token::Number(seconds))) => {
+                    self.position += 1;
+                    duration = Some(seconds);
+                    self.note_source("duration", clause_start);
+                },
+                (Some("to") | Some("as") | Some("into"), Some(Token::Path(_) | Token::Format(_))) => {
+                    output_path = Some(self.parse_output_path_or_format(&input_path)?);
+                },
+                (Some("width"), Some(Token::Number(number))) => {
+                    self.position += 1;
+                    options.width = parse_dimension(&number.to_string())?;
+                    self.note_source("width", clause_start);
+                },
+                (None | Some("at"), Some(Token::Number(number))) => {
+                    self.position += 1;
+                    if self.accept_word(&["fps"]).is_some() {
+                        options.fps = parse_frame_rate(&number.to_string())?;
+                        self.note_source("fps", clause_start);
+                    } else if self.accept_word(&["wide", "px", "pixels"]).is_some() {
+                        self.accept_word(&["wide"]);
+                        options.width = parse_dimension(&number.to_string())?;
+                        self.note_source("width", clause_start);
+                    } else if self.peek_word().is_some() && !self.at_step_break() {
+                        return Err(ParseError::UnexpectedToken(format!(
+                            "expected 'fps' or 'wide' after {}, got: {}",
+                            number,
+                            self.peek_word().unwrap_or_default()
+                        )));
+                    } else {
+                        self.position = clause_start;
+                        break;
+                    }
+                },
+                (None | Some("at"), Some(Token::Word(word))) if word.ends_with("fps") => {
+                    self.position += 1;
+                    options.fps = parse_frame_rate(word.trim_end_matches("fps"))?;
+                    self.note_source("fps", clause_start);
+                },
+                _ => {
+                    self.position = clause_start;
+                    break;
+                },
+            }
+        }
+
+        options.range = match (start, end, duration) {
+            (None, None, None) => None,
+            (start, end, None) => Some(TimeRange::between(start, end)?),
+            (start, None, Some(duration)) => Some(TimeRange::starting_for(start, duration)?),
+            (_, Some(_), Some(_)) => {
+                return Err(ParseError::InvalidParameter(
+                    "a gif takes either an end time or a length, not both".to_string(),
+                ));
+            },
+        };
+
+        let output_path = match output_path {
+            Some(output_path) => output_path,
+            None => derive_output_path(&input_path, "", "gif")?,
+        };
+
+        Ok(Intent {
+            operation: Operation::Gif(options),
+            input_paths: vec![PathBuf::from(input_path)],
+            output_path: PathBuf::from(output_path),
+            sources: HashMap::new(),
+        })
+    }
+
+    /// Parses the rest of an audio extraction.
+    ///
+    /// Accepts `audio from <file>` followed, in any order, by `as|to <output>`
+    /// (a path, `.ext` or bare format word), `as lossless [flac]`, `at <n>
+    /// kbps` and `without re-encoding`, which choose the `Quality`; a bitrate
+    /// and copying rule each other out. The default output is MP3, FLAC when
+    /// lossless, and MKA (which holds any audio codec) when copying.
+    fn parse_extract_audio(&mut self) -> Result<Intent, ParseError> {
+        self.accept_word(&["the"]);
+        self.accept_word(&["audio", "sound", "soundtrack"]);
+        self.accept_word(&["track"]);
+        self.accept_before_input(&["from", "of"]);
+        let input_path = self.parse_input_path()?;
+
+        let mut bitrate = None;
+        let mut copy = false;
+        let mut output_path = None;
+        let mut lossless = false;
+
+        loop {
+            let start = self.position;
+            if self.accept_word(&["without"]).is_some() {
+                self.accept_word(&["re-encoding", "reencoding", "transcoding", "conversion"])
+                    .ok_or_else(|| ParseError::MissingToken("re-encoding".to_string()))?;
+                copy = true;
+                self.note_source("copy", start);
+            } else if self.accept_word(&["at"]).is_some() {
+                bitrate = Some(self.parse_bitrate()?);
+                self.note_source("bitrate", start);
+            } else if self.accept_word(&["as", "to", "into", "in"]).is_some() {
+                if self.accept_word(&["lossless"]).is_some() {
+                    lossless = true;
+                    self.accept_word(&["flac"]);
+                    self.note_source("output", start);
+                } else if let Some(format) = self.accept_word(AUDIO_FORMATS) {
+                    output_path = Some(derive_output_path(&input_path, "", &format)?);
+                    self.note_source("output", start);
                 } else {
-                    Err(ParseError::UnexpectedToken(format!("Expected '{}', got: {}", expected, word)))
+                    output_path = Some(self.parse_output_path_or_format(&input_path)?);
                 }
-            },
-            _ => Err(ParseError::UnexpectedToken(format!("Expected '{}', got different token type", expected))),
+            } else {
+                break;
+            }
         }
-    }
 
-    fn parse_output_path_or_format(&mut self, input_path: &str) -> Result<String, ParseError> {
-        if self.position >= self.tokens.len() {
-            return Err(ParseError::MissingToken("output path or format".to_string()));
+        let output_path = match output_path {
+            Some(output_path) => output_path,
+            None if lossless => derive_output_path(&input_path, "", "flac")?,
+            None if copy => derive_output_path(&input_path, "", "mka")?,
+            None => derive_output_path(&input_path, "", "mp3")?,
+        };
+
+        if !file_utils::is_supported_format(&output_path) {
+            return Err(ParseError::UnsupportedFormat(output_path));
         }
 
-        match &self.tokens[self.position] {
-            Token::Path(path) => {
+        let quality = match (copy, bitrate) {
+            (true, Some(_)) => {
+                return Err(ParseError::InvalidParameter(
+                    "a bitrate cannot be set when copying audio without re-encoding".to_string(),
+                ));
+            },
+            (true, None) => Quality::Copy { source: None },
+            (false, Some(bitrate)) => Quality::Bitrate(bitrate),
+            (false, None) => Quality::Default,
+        };
+
+        Ok(Intent {
+            operation: Operation::ExtractAudio(quality),
+            input_paths: vec![PathBuf::from(input_path)],
+            output_path: PathBuf::from(output_path),
+            sources: HashMap::new(),
+        })
+    }
+
+    /// Parses an audio bitrate such as `192 kbps`, `192kbps` or `192k`.
+    fn parse_bitrate(&mut self) -> Result<Bitrate, ParseError> {
+        let (value, unit) = match self.peek().cloned() {
+            Some(Token::Number(number)) => {
                 self.position += 1;
-                Ok(path.clone())
+                let unit = self.accept_word(&["kbps", "kbit", "kbits", "kb", "k"]).unwrap_or_default();
+                (number.to_string(), unit)
             },
-            Token::Format(format) => {
+            Some(Token::Word(word)) => {
+                let digits = word.chars().take_while(char::is_ascii_digit).count();
                 self.position += 1;
-                
-                let input_path_buf = PathBuf::from(input_path);
-                let dir = input_path_buf.parent().unwrap_or_else(|| std::path::Path::new(""));
-                let base_name = input_path_buf.file_stem()
-                    .ok_or_else(|| ParseError::InvalidPath(input_path.to_string()))?;
-                
-                let dir_str = dir.to_string_lossy();
-                let base_name_str = base_name.to_string_lossy();
-                let format_str = format.trim_start_matches('.');
-
-                let new_path = if dir_str.is_empty() {
-                    format!("{}.{}", base_name_str, format_str)
-                } else {
-                    format!("{}/{}.{}", dir_str, base_name_str, format_str)
-                };
-                
-                Ok(new_path)
+                (word[..digits].to_string(), word[digits..].to_string())
             },
-            Token::Word(word) => {
-                if word.contains('.') {
+            Some(_) => return Err(ParseError::UnexpectedToken("Expected a bitrate such as 192 kbps".to_string())),
+            None => return Err(ParseError::MissingToken("bitrate".to_string())),
+        };
+
+        match (value.parse::<u32>(), unit.as_str()) {
+            (Ok(kbps), "" | "k" | "kb" | "kbps" | "kbit" | "kbits") => Ok(Bitrate::from_kbps(kbps)?),
+            _ => Err(ParseError::InvalidParameter(format!(
+                "'{}{}' is not a valid audio bitrate; use something like 192 kbps", value, unit
+            ))),
+        }
+    }
+
+    /// Parses the file named by `inspect <file>` or `what is in <file>`.
+    fn parse_inspect(&mut self) -> Result<Intent, ParseError> {
+        self.accept_word(&["the"]);
+        let input_path = self.parse_input_path()?;
+
+        Ok(Intent {
+            operation: Operation::Inspect,
+            input_paths: vec![PathBuf::from(input_path)],
+            output_path: PathBuf::new(),
+            sources: HashMap::new(),
+        })
+    }
+
+    /// Parses the rest of a single-frame grab.
+    ///
+    /// Accepts `a frame from <file> at <time> as <image>` and `a thumbnail of
+    /// <file>`. Without a time, ffmpeg's `thumbnail` filter picks a
+    /// representative frame.
+    fn parse_thumbnail(&mut self) -> Result<Intent, ParseError> {
+        self.accept_word(&["a", "an", "the"]);
+        self.accept_word(&["frame", "screenshot", "still", "snapshot", "thumbnail", "poster"]);
+        self.accept_before_input(&["from", "of"]);
+        let input_path = self.parse_input_path()?;
+
+        let start = self.position;
+        let time = match self.accept_word(&["at"]) {
+            Some(_) => Some(TimePoint::new(self.expect_time("time of the frame")?)?),
+            None => None,
+        };
+        self.note_source("time", start);
+
+        let output_path = if self.accept_word(&["as", "to", "into"]).is_some() {
+            self.parse_output_path_or_format(&input_path)?
+        } else {
+            derive_output_path(&input_path, "_thumbnail", "jpg")?
+        };
+
+        if !file_utils::is_supported_format(&output_path) {
+            return Err(ParseError::UnsupportedFormat(output_path));
+        }
+
+        Ok(Intent {
+            operation: Operation::Thumbnail { time },
+            input_paths: vec![PathBuf::from(input_path)],
+            output_path: PathBuf::from(output_path),
+            sources: HashMap::new(),
+        })
+    }
+
+    /// Parses the rest of a frame extraction into an `fps` parameter and a
+    /// numbered image sequence output.
+    ///
+    /// Accepts `one frame every 10 seconds from <file> into <dir>`, `2 frames
+    /// per second from <file>` and `frames from <file> as png`. The default
+    /// rate is one frame per second and the default image format is JPEG.
+    fn parse_frames(&mut self) -> Result<Intent, ParseError> {
+        let mut fps = FrameRate::per_second(1.0)?;
+        let start = self.position;
+
+        let count = match self.peek() {
+            Some(Token::Number(count)) if *count <= 0.0 => return Err(ParamError::FrameCount(*count).into()),
+            Some(Token::Number(count)) => {
+                let count = *count;
+                self.position += 1;
+                Some(count)
+            },
+            _ => self.accept_word(&["one", "a", "all"]).map(|_| 1.0),
+        };
+        self.accept_word(&["frame", "frames", "stills", "images"]);
+
+        if self.accept_word(&["every"]).is_some() {
+            let interval = match self.peek() {
+                Some(Token::Time(seconds)) | Some(Token::Number(seconds)) => {
+                    let seconds = *seconds;
                     self.position += 1;
-                    Ok(word.clone())
-                } else {
-                    Err(ParseError::UnexpectedToken(format!("Expected path or format, got: {}", word)))
+                    seconds
+                },
+                _ => self.accept_word(&["second", "minute", "hour"])
+                    .and_then(|unit| time::unit_seconds(&unit))
+                    .ok_or_else(|| ParseError::MissingToken("interval".to_string()))?,
+            };
+            fps = FrameRate::every(interval / count.unwrap_or(1.0))?;
+        } else if self.accept_word(&["per", "a"]).is_some() {
+            self.accept_word(&["second"]).ok_or_else(|| ParseError::MissingToken("second".to_string()))?;
+            fps = parse_frame_rate(&count.unwrap_or(1.0).to_string())?;
+        } else if self.accept_word(&["fps"]).is_some() {
+            fps = parse_frame_rate(&count.unwrap_or(1.0).to_string())?;
+        }
+        self.note_source("fps", start);
+
+        self.accept_before_input(&["from", "of"]);
+        let input_path = self.parse_input_path()?;
+
+        let mut directory = None;
+        let mut extension = "jpg".to_string();
+        let mut output_path = None;
+        let output_start = self.position;
+        while let Some(word) = self.accept_word(&["into", "to", "in", "as"]) {
+            match self.peek().cloned() {
+                Some(Token::Format(format)) => {
+                    self.position += 1;
+                    extension = format.trim_start_matches('.').to_string();
+                },
+                Some(Token::Word(format)) if word == "as" => {
+                    self.position += 1;
+                    extension = format;
+                },
+                Some(Token::Path(path)) if PathBuf::from(&path).extension().is_none() => {
+                    self.position += 1;
+                    directory = Some(path);
+                },
+                Some(Token::Path(path)) => {
+                    self.position += 1;
+                    self.named_output = Some(path.clone());
+                    output_path = Some(path);
+                },
+                _ => return Err(ParseError::MissingToken("folder or image format".to_string())),
+            }
+        }
+        self.note_source("output", output_start);
+
+        let output_path = match output_path {
+            Some(output_path) => output_path,
+            None => {
+                let stem = PathBuf::from(&input_path).file_stem().unwrap_or_default().to_string_lossy().into_owned();
+                let pattern = format!("{}_%04d.{}", stem, extension);
+                match directory {
+                    Some(directory) => PathBuf::from(directory).join(pattern).to_string_lossy().into_owned(),
+                    None => derive_output_path(&input_path, "_%04d", &extension)?,
                 }
             },
-            _ => Err(ParseError::UnexpectedToken("Expected output path or format".to_string())),
+        };
+
+        if !file_utils::is_supported_format(&output_path) {
+            return Err(ParseError::UnsupportedFormat(output_path));
         }
+
+        Ok(Intent {
+            operation: Operation::ExtractFrames(fps),
+            input_paths: vec![PathBuf::from(input_path)],
+            output_path: PathBuf::from(output_path),
+            sources: HashMap::new(),
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::grammar::tokenizer::{Tokenizer};
+    /// Parses `from <time>`, `to <time>` and `for <duration>` into a time
+    /// range. At least one bound is required.
+    fn parse_time_range(&mut self) -> Result<TimeRange, ParseError> {
+        let clause_start = self.position;
+        let start = match self.accept_word(&["from", "at"]) {
+            Some(_) => Some(self.expect_time("start time")?),
+            None => None,
+        };
+        self.note_source("start", clause_start);
 
-    #[test]
-    fn test_parse_convert_command() {
-        let mut tokenizer = Tokenizer::new("convert video.mp4 to video.avi");
-        let tokens = tokenizer.tokenize();
-        let mut parser = Parser::new(tokens);
-        let intent = parser.parse().unwrap();
+        let clause_start = self.position;
+        let range = match self.accept_word(&["to", "until", "till", "for"]).as_deref() {
+            Some("for") => {
+                let duration = self.expect_time("duration")?;
+                self.note_source("duration", clause_start);
+                TimeRange::starting_for(start, duration)?
+            },
+            Some(_) => {
+                let end = self.expect_time("end time")?;
+                self.note_source("end", clause_start);
+                TimeRange::between(start, Some(end))?
+            },
+            None if start.is_some() => TimeRange::between(start, None)?,
+            None => return Err(ParseError::MissingToken("from <time> or to <time>".to_string())),
+        };
 
-        assert_eq!(intent.operation, OperationType::Convert);
-        assert_eq!(intent.input_path, PathBuf::from("video.mp4"));
-        assert_eq!(intent.output_path, PathBuf::from("video.avi"));
+        Ok(range)
     }
 
-    #[test]
-    fn test_parse_convert_with_format() {
-        let mut tokenizer = Tokenizer::new("convert video.mp4 to .avi");
-        let tokens = tokenizer.tokenize();
-        let mut parser = Parser::new(tokens);
-        let intent = parser.parse().unwrap();
+    /// Parses an optional word choosing between a frame-accurate re-encode
+    /// and a fast stream copy.
+    fn parse_trim_mode(&mut self) -> Option<TrimMode> {
+        let start = self.position;
+        let mode = if self.accept_word(&["precisely", "accurately", "exactly", "frame-accurate"]).is_some() {
+            Some(TrimMode::Accurate)
+        } else if self.peek_word() == Some("frame")
+            && matches!(self.tokens.get(self.position + 1), Some(Token::Word(word)) if word.starts_with("accurate"))
+        {
+            self.position += 2;
+            Some(TrimMode::Accurate)
+        } else if self.accept_word(&["fast", "quickly"]).is_some() {
+            Some(TrimMode::Copy)
+        } else {
+            None
+        };
+        self.note_source("mode", start);
+        mode
+    }
 
-        assert_eq!(intent.operation, OperationType::Convert);
-        assert_eq!(intent.input_path, PathBuf::from("video.mp4"));
-        assert_eq!(intent.output_path, PathBuf::from("video.avi"));
+    /// Parses a timestamp or duration; a bare number counts as seconds.
+    fn expect_time(&mut self, what: &str) -> Result<f64, ParseError> {
+        match self.peek() {
+            Some(Token::Time(seconds)) | Some(Token::Number(seconds)) => {
+                let seconds = *seconds;
+                self.position += 1;
+                Ok(seconds)
+            },
+            Some(_) => Err(ParseError::UnexpectedToken(format!("Expected {}", what))),
+            None => Err(ParseError::MissingToken(what.to_string())),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_word(&self) -> Option<&str> {
+        match self.peek() {
+            Some(Token::Word(word)) => Some(word.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Consumes the next token if it is one of the given words.
+    fn accept_word(&mut self, words: &[&str]) -> Option<String> {
+        let word = self.peek_word().filter(|word| words.contains(word))?.to_string();
+        self.position += 1;
+        Some(word)
+    }
+
+    /// Remembers the words from `start` up to the current position as the source of `key`.
+    fn note_source(&mut self, key: &str, start: usize) {
+        self.note_source_between(key, start, self.position);
+    }
+
+    /// Remembers the words from `start` up to `end` as the source of `key`.
+    ///
+    /// The words are cut from the command as written when it is known, and
+    /// otherwise rebuilt from the tokens. A key noted more than once, such as
+    /// the inputs of a join, collects every phrase. Nothing is noted when no
+    /// words were consumed.
+    fn note_source_between(&mut self, key: &str, start: usize, end: usize) {
+        if start >= end {
+            return;
+        }
+
+        let written = match (&self.command, self.spans.get(start), self.spans.get(end - 1)) {
+            (Some(command), Some(first), Some(last)) => command.get(first.start..last.end),
+            _ => None,
+        };
+        let words = match written {
+            Some(words) => words.to_string(),
+            None => self.tokens[start..end].iter().map(Token::to_string).collect::<Vec<_>>().join(" "),
+        };
+        self.sources.entry(key.to_string())
+            .and_modify(|source| {
+                source.push_str(", ");
+                source.push_str(&words);
+            })
+            .or_insert(words);
+    }
+
+    /// Consumes the words joining two steps of a chain, such as `, and then`.
+    ///
+    /// Returns whether a step break was found; it must be followed by an
+    /// operation word, so `a.mp4 and b.mp4` in a join is left alone.
+    fn accept_step_break(&mut self) -> bool {
+        let start = self.position;
+        if matches!(self.peek(), Some(Token::Comma)) {
+            self.position += 1;
+        }
+        self.accept_word(&["and"]);
+        self.accept_word(&["then", "afterwards"]);
+        self.accept_word(FILLERS);
+
+        if self.position > start && self.peek_word().and_then(verb).is_some() {
+            return true;
+        }
+        self.position = start;
+        false
     }
-}
\ No newline at end of file
+
+    /// Returns whether the next words start another step of a chain.
+    fn at_step_break(&mut self) -> bool {
+        let start = self.position;
+        let found = self.accept_step_break();
+        self.position = start;
+        found
+    }
+
+    /// Consumes `it`, `the result` or a similar reference to the previous step's output.
+    fn accept_reference(&mut self) -> bool {
+        let start = self.position;
+        self.accept_word(&["the"]);
+        if self.accept_word(REFERENCES).is_some() {
+            return true;
+        }
+        self.position = start;
+        false
+    }
+
+    /// Returns whether the token at `index` names an input: a path, or a
+    /// reference to the previous result within a chain.
+    fn names_input_at(&self, index: usize) -> bool {
+        let is_reference = |token: Option<&Token>| {
+            matches!(token, Some(Token::Word(word)) if REFERENCES.contains(&word.as_str()))
+        };
+        match self.tokens.get(index) {
+            Some(Token::Path(_)) => true,
+            Some(Token::Word(word)) if word.contains('.') => true,
+            Some(Token::Word(word)) if word == "the" => {
+                self.previous_output.is_some() && is_reference(self.tokens.get(index + 1))
+            },
+            token => self.previous_output.is_some() && is_reference(token),
+        }
+    }
+
+    /// Consumes one of `words` when it introduces the input, as `from` does in
+    /// `a gif from clip.mp4`. Within a chain the input may be left out, so
+    /// the word is left for a time or output clause (`a gif from 0:05`)
+    /// unless a path or `it` follows.
+    fn accept_before_input(&mut self, words: &[&str]) -> Option<String> {
+        if self.previous_output.is_some() && !self.names_input_at(self.position + 1) {
+            return None;
+        }
+        self.accept_word(words)
+    }
+
+    fn next_is_output(&self) -> bool {
+        matches!(self.peek(), Some(Token::Path(_)) | Some(Token::Format(_)))
+    }
+
+    fn expect_number(&mut self, what: &str) -> Result<f64, ParseError> {
+        match self.peek() {
+            Some(Token::Number(number)) => {
+                let number = *number;
+                self.position += 1;
+                Ok(number)
+            },
+            Some(_) => Err(ParseError::UnexpectedToken(format!("Expected {}", what))),
+            None => Err(ParseError::MissingToken(what.to_string())),
+        }
+    }
+
+    /// Parses a target size for a resize.
+    ///
+    /// Accepts `1280x720`, `1280 x 720`, `720p`, `half size`, `width 640`,
+    /// `height 360`, `640 wide` and `360 tall`. A side that is not given
+    /// follows the aspect ratio.
+    fn parse_dimensions(&mut self) -> Result<Resolution, ParseError> {
+        let resolution = match self.peek().cloned() {
+            Some(Token::Word(word)) if parse_size(&word).is_some() => {
+                self.position += 1;
+                let (width, height) = parse_size(&word).ok_or_else(|| ParseError::MissingToken("size".to_string()))?;
+                Resolution::Exact { width, height }
+            },
+            Some(Token::Word(word)) if word.len() > 1 && word.ends_with('p') => {
+                self.position += 1;
+                Resolution::Height(parse_dimension(&word[..word.len() - 1])?)
+            },
+            Some(Token::Word(word)) if word == "4k" => {
+                self.position += 1;
+                Resolution::exact(3840, 2160)?
+            },
+            Some(Token::Word(word)) if scale_expression(&word).is_some() => {
+                self.position += 1;
+                self.accept_word(&["the"]);
+                self.accept_word(&["size", "resolution"]);
+                scale_expression(&word).unwrap_or_default()
+            },
+            Some(Token::Word(word)) if word == "width" || word == "height" => {
+                self.position += 1;
+                let value = parse_dimension(&self.expect_number(&word)?.to_string())?;
+                if self.accept_word(&["and"]).is_some() {
+                    let other = if word == "width" { "height" } else { "width" };
+                    self.accept_word(&[other])
+                        .ok_or_else(|| ParseError::MissingToken(other.to_string()))?;
+                    let other_value = parse_dimension(&self.expect_number(other)?.to_string())?;
+                    if word == "width" {
+                        Resolution::Exact { width: value, height: other_value }
+                    } else {
+                        Resolution::Exact { width: other_value, height: value }
+                    }
+                } else if word == "width" {
+                    Resolution::Width(value)
+                } else {
+                    Resolution::Height(value)
+                }
+            },
+            Some(Token::Number(number)) => {
+                self.position += 1;
+                let first = parse_dimension(&number.to_string())?;
+                if self.accept_word(&["x", "by"]).is_some() {
+                    let second = parse_dimension(&self.expect_number("height")?.to_string())?;
+                    Resolution::Exact { width: first, height: second }
+                } else if self.accept_word(&["wide", "width"]).is_some() {
+                    Resolution::Width(first)
+                } else if self.accept_word(&["tall", "high", "height"]).is_some() {
+                    Resolution::Height(first)
+                } else {
+                    return Err(ParseError::UnexpectedToken(format!(
+                        "Expected 'x', 'wide' or 'tall' after {}", number
+                    )));
+                }
+            },
+            Some(_) => {
+                return Err(ParseError::UnexpectedToken(
+                    "Expected a size such as 1280x720, 720p, half size or width 640".to_string(),
+                ));
+            },
+            None => return Err(ParseError::MissingToken("size".to_string())),
+        };
+
+        self.skip_aspect_ratio_phrase();
+
+        Ok(resolution)
+    }
+
+    /// Parses an optional codec clause into a video and an audio codec.
+    ///
+    /// Accepts `using h265 and opus`, where each codec's kind comes from the
+    /// codec table, and `with video codec vp9 and audio codec opus`.
+    fn parse_codecs(&mut self) -> Result<(Option<Codec>, Option<Codec>), ParseError> {
+        let (mut video, mut audio) = (None, None);
+        if self.accept_word(&["using", "with"]).is_none() {
+            return Ok((video, audio));
+        }
+
+        let mut start = self.position - 1;
+        loop {
+            let requested_kind = match self.accept_word(&["video", "audio"]).as_deref() {
+                Some("video") => Some(CodecKind::Video),
+                Some(_) => Some(CodecKind::Audio),
+                None => None,
+            };
+            if requested_kind.is_some() {
+                self.accept_word(&["codec"]);
+            }
+
+            let name = match self.peek() {
+                Some(Token::Word(word)) => word.clone(),
+                Some(_) => return Err(ParseError::UnexpectedToken("Expected codec name".to_string())),
+                None => return Err(ParseError::MissingToken("codec".to_string())),
+            };
+
+            let codec = codecs::lookup_codec(&name).ok_or(ParseError::UnknownCodec(name))?;
+            if requested_kind.is_some_and(|kind| kind != codec.kind) {
+                return Err(ParseError::InvalidParameter(format!(
+                    "{} is not a {} codec",
+                    codec.name,
+                    if codec.kind == CodecKind::Video { "audio" } else { "video" }
+                )));
+            }
+            self.position += 1;
+
+            let key = match codec.kind {
+                CodecKind::Video => {
+                    video = Some(codec);
+                    "vcodec"
+                },
+                CodecKind::Audio => {
+                    audio = Some(codec);
+                    "acodec"
+                },
+            };
+            self.note_source(key, start);
+
+            if self.at_step_break() || self.accept_word(&["and"]).is_none() {
+                return Ok((video, audio));
+            }
+            start = self.position;
+        }
+    }
+
+    /// Skips phrases such as `keeping aspect ratio` or `and preserve the aspect ratio`.
+    ///
+    /// Aspect preservation is already the default for a single given side.
+    fn skip_aspect_ratio_phrase(&mut self) {
+        let start = self.position;
+        self.accept_word(&["and", "while"]);
+        if self.accept_word(&["keeping", "keep", "preserving", "preserve", "maintaining", "maintain"]).is_some() {
+            self.accept_word(&["the"]);
+            if self.accept_word(&["aspect"]).is_some() {
+                self.accept_word(&["ratio"]);
+            } else {
+                self.position = start;
+            }
+        } else {
+            self.position = start;
+        }
+    }
+
+    /// Parses an optional `as <path|format>` clause.
+    ///
+    /// Without one, the output sits next to the input with `suffix` appended
+    /// to the file stem, e.g. `clip.mp4` becomes `clip_resized.mp4`.
+    fn parse_optional_output(&mut self, input_path: &str, suffix: &str) -> Result<String, ParseError> {
+        if self.accept_word(&["as"]).is_some() {
+            return self.parse_output_path_or_format(input_path);
+        }
+
+        let input_path_buf = PathBuf::from(input_path);
+        let extension = input_path_buf.extension().unwrap_or_default().to_string_lossy();
+        derive_output_path(input_path, &format!("_{}", suffix), &extension)
+    }
+
+    fn parse_operation(&mut self) -> Result<OperationType, ParseError> {
+        self.accept_word(FILLERS);
+        if self.position >= self.tokens.len() {
+            return Err(ParseError::MissingToken("operation".to_string()));
+        }
+
+        match self.tokens[self.position].clone() {
+            Token::Word(word) => {
+                self.position += 1;
+
+                match (word.as_str(), verb(&word)) {
+                    (_, Some(Verb::Names(operation))) => Ok(operation.clone()),
+                    (_, Some(Verb::Extract)) if self.next_names_frames() => Ok(OperationType::ExtractFrames),
+                    (_, Some(Verb::Extract)) => Ok(OperationType::ExtractAudio),
+                    (_, Some(Verb::Make)) => self.parse_product(),
+                    ("what", None) => {
+                        self.accept_word(&["is", "are"]);
+                        self.accept_word(&["in", "inside"])
+                            .ok_or_else(|| ParseError::MissingToken("in".to_string()))?;
+                        Ok(OperationType::Inspect)
+                    },
+                    _ => {
+                        self.position -= 1;
+                        Err(ParseError::UnexpectedToken(format!("Unknown operation: {}", word)))
+                    },
+                }
+            },
+            _ => Err(ParseError::UnexpectedToken("Expected operation word".to_string())),
+        }
+    }
+
+    /// Parses what a `make`/`create` command produces, e.g. `a gif`. A file
+    /// right after the verb is converted instead, as in `make clip.mov into
+    /// an mp4`.
+    fn parse_product(&mut self) -> Result<OperationType, ParseError> {
+        if self.names_input_at(self.position) || self.names_input_at(self.position + 1) {
+            return Ok(OperationType::Convert);
+        }
+        self.accept_word(&["a", "an"]);
+        self.accept_word(&["animated"]);
+
+        match self.peek_word() {
+            Some("gif") => {
+                self.position += 1;
+                Ok(OperationType::Gif)
+            },
+            Some("thumbnail" | "screenshot" | "still" | "poster") => Ok(OperationType::Thumbnail),
+            Some(word) => Err(ParseError::UnexpectedToken(format!("Don't know how to make: {}", word))),
+            None => Err(ParseError::MissingToken("what to make".to_string())),
+        }
+    }
+
+    /// Returns whether the words after `extract` ask for frames rather than audio.
+    fn next_names_frames(&self) -> bool {
+        matches!(self.peek(), Some(Token::Number(_)))
+            || matches!(
+                self.peek_word(),
+                Some("one" | "a" | "frame" | "frames" | "every" | "all" | "stills" | "images")
+            )
+    }
+
+    fn parse_path(&mut self) -> Result<String, ParseError> {
+        if self.position >= self.tokens.len() {
+            return Err(ParseError::MissingToken("path".to_string()));
+        }
+
+        match &self.tokens[self.position] {
+            Token::Path(path) => {
+                self.position += 1;
+                Ok(path.clone())
+            },
+            Token::Word(word) => {
+                if word.contains('.') {
+                    self.position += 1;
+                    Ok(word.clone())
+                } else {
+                    Err(ParseError::UnexpectedToken(format!("Expected path, got: {}", word)))
+                }
+            },
+            _ => Err(ParseError::UnexpectedToken("Expected path".to_string())),
+        }
+    }
+
+    fn expect_word(&mut self, expected: &str) -> Result<(), ParseError> {
+        if self.position >= self.tokens.len() {
+            return Err(ParseError::MissingToken(expected.to_string()));
+        }
+
+        match &self.tokens[self.position] {
+            Token::Word(word) => {
+                if word == expected {
+                    self.position += 1;
+                    Ok(())
+                } else {
+                    Err(ParseError::UnexpectedToken(format!("Expected '{}', got: {}", expected, word)))
+                }
+            },
+            _ => Err(ParseError::UnexpectedToken(format!("Expected '{}', got different token type", expected))),
+        }
+    }
+
+    fn parse_output_path_or_format(&mut self, input_path: &str) -> Result<String, ParseError> {
+        if self.position >= self.tokens.len() {
+            return Err(ParseError::MissingToken("output path or format".to_string()));
+        }
+
+        let start = match self.position.checked_sub(1).map(|index| &self.tokens[index]) {
+            Some(Token::Word(word)) if matches!(word.as_str(), "to" | "as" | "into" | "in") => self.position - 1,
+            _ => self.position,
+        };
+        if self.position + 1 < self.tokens.len() {
+            self.accept_word(&["a", "an", "the"]);
+        }
+
+        let output_path = match &self.tokens[self.position] {
+            Token::Path(path) => {
+                self.position += 1;
+                self.named_output = Some(path.clone());
+                Ok(path.clone())
+            },
+            Token::Format(format) => {
+                let format = format.clone();
+                self.position += 1;
+                self.accept_word(&["format", "file", "version"]);
+
+                derive_format_output(input_path, format.trim_start_matches('.'))
+            },
+            Token::Word(word) => {
+                if word.contains('.') {
+                    self.position += 1;
+                    self.named_output = Some(word.clone());
+                    Ok(word.clone())
+                } else if file_utils::is_supported_extension(word) {
+                    let format = word.clone();
+                    self.position += 1;
+                    self.accept_word(&["format", "file", "version"]);
+
+                    derive_format_output(input_path, &format)
+                } else {
+                    Err(ParseError::UnexpectedToken(format!("Expected path or format, got: {}", word)))
+                }
+            },
+            _ => Err(ParseError::UnexpectedToken("Expected output path or format".to_string())),
+        }?;
+
+        self.note_source("output", start);
+        Ok(output_path)
+    }
+
+}
+
+/// Where in the token stream a batch of files is named, and what it matches.
+#[derive(Debug)]
+struct BatchSource {
+    start: usize,
+    end: usize,
+    directory: PathBuf,
+    pattern: String,
+}
+
+/// Removes a trailing `into <directory>` from a batch command and returns the directory.
+///
+/// Only a target without an extension counts, so `into joined.mp4` is left
+/// for the operation to parse as its output file.
+fn take_output_directory(tokens: &mut Vec<(Token, Range<usize>)>) -> Option<PathBuf> {
+    let index = tokens.iter().position(|(token, _)| matches!(token, Token::Word(word) if word == "into"))?;
+    let directory = match &tokens.get(index + 1)?.0 {
+        Token::Path(path) if Path::new(path).extension().is_none() => PathBuf::from(path),
+        Token::Word(word) if !file_utils::is_supported_extension(word) => PathBuf::from(word),
+        _ => return None,
+    };
+
+    tokens.drain(index..index + 2);
+    Some(directory)
+}
+
+/// Audio formats that can be named as a bare word, as in `as mp3`.
+const AUDIO_FORMATS: &[&str] = &["mp3", "wav", "flac", "m4a", "aac", "opus", "ogg", "mka"];
+
+/// What a verb says about the operation it starts.
+enum Verb {
+    /// The verb names the operation outright
+    Names(OperationType),
+    /// Frames or audio, depending on the words that follow
+    Extract,
+    /// Whatever is named next, as in `make a gif`
+    Make,
+}
+
+/// The verbs that start an operation, which are also the words that start
+/// a step, so `and then` or a comma before them begins a new one.
+const VERBS: &[(&str, Verb)] = &[
+    ("convert", Verb::Names(OperationType::Convert)),
+    ("turn", Verb::Names(OperationType::Convert)),
+    ("change", Verb::Names(OperationType::Convert)),
+    ("export", Verb::Names(OperationType::Convert)),
+    ("save", Verb::Names(OperationType::Convert)),
+    ("resize", Verb::Names(OperationType::Resize)),
+    ("transcode", Verb::Names(OperationType::Transcode)),
+    ("extract", Verb::Extract),
+    ("extractaudio", Verb::Names(OperationType::ExtractAudio)),
+    ("grab", Verb::Names(OperationType::Thumbnail)),
+    ("take", Verb::Names(OperationType::Thumbnail)),
+    ("capture", Verb::Names(OperationType::Thumbnail)),
+    ("snap", Verb::Names(OperationType::Thumbnail)),
+    ("trim", Verb::Names(OperationType::Trim)),
+    ("cut", Verb::Names(OperationType::Trim)),
+    ("keep", Verb::Names(OperationType::Trim)),
+    ("join", Verb::Names(OperationType::Concat)),
+    ("concat", Verb::Names(OperationType::Concat)),
+    ("concatenate", Verb::Names(OperationType::Concat)),
+    ("merge", Verb::Names(OperationType::Concat)),
+    ("combine", Verb::Names(OperationType::Concat)),
+    ("make", Verb::Make),
+    ("create", Verb::Make),
+    ("generate", Verb::Make),
+    ("inspect", Verb::Names(OperationType::Inspect)),
+    ("probe", Verb::Names(OperationType::Inspect)),
+    ("analyze", Verb::Names(OperationType::Inspect)),
+    ("analyse", Verb::Names(OperationType::Inspect)),
+];
+
+/// Looks up a word in `VERBS`.
+fn verb(word: &str) -> Option<&'static Verb> {
+    VERBS.iter().find(|(verb, _)| *verb == word).map(|(_, meaning)| meaning)
+}
+
+/// Words that refer to the previous step's output within a chain, as in `resize it`.
+const REFERENCES: &[&str] = &["it", "that", "this", "result", "output"];
+
+/// Words that may introduce the target of a conversion, as in `turn clip.mov into gif`.
+const TARGETS: &[&str] = &["to", "into", "as"];
+
+/// Words that may come before a file name without changing it, as in `convert my video.mp4`.
+const DETERMINERS: &[&str] = &["the", "my", "our", "your", "this", "that", "a", "an"];
+
+/// Polite words that may start a step, as in `please convert ...`.
+const FILLERS: &[&str] = &["please", "just", "also", "now"];
+
+/// Other words the grammar knows, which a misspelled word may be suggested as.
+const KEYWORDS: &[&str] = &[
+    "to", "from", "as", "into", "using", "with", "and", "then", "for", "at", "of", "in", "until", "the", "first",
+    "last", "audio", "video", "codec", "frame", "frames", "every", "per", "second", "seconds", "minutes", "fps",
+    "wide", "tall", "width", "height", "half", "quarter", "double", "size", "gif", "thumbnail", "screenshot",
+    "precisely", "fast", "without", "re-encoding", "lossless", "kbps", "all", "files", "format",
+];
+
+/// Returns the text of a token that holds words, as the user typed it.
+fn token_text(token: &Token) -> Option<&str> {
+    match token {
+        Token::Word(text) | Token::Path(text) | Token::Format(text) | Token::Unknown(text) => Some(text),
+        Token::Number(_) | Token::Time(_) | Token::Comma => None,
+    }
+}
+
+/// Suggests a known codec, format or word close to the offending token's text.
+fn suggest(error: &ParseError, token: &Token, text: &str) -> Option<String> {
+    match (error, token) {
+        (ParseError::UnknownCodec(_), _) => {
+            diagnostic::closest_word(text, &codecs::known_codec_names()).map(str::to_string)
+        },
+        (ParseError::UnsupportedFormat(_), _) => {
+            let (stem, extension) = text.rsplit_once('.')?;
+            diagnostic::closest_word(extension, file_utils::SUPPORTED_EXTENSIONS)
+                .map(|extension| format!("{}.{}", stem, extension))
+        },
+        (_, Token::Word(word)) => {
+            let verbs: Vec<&str> = VERBS.iter().map(|(verb, _)| *verb).collect();
+            let vocabulary = [&verbs, KEYWORDS, FILLERS, file_utils::SUPPORTED_EXTENSIONS, &codecs::known_codec_names()].concat();
+            if vocabulary.contains(&word.as_str()) {
+                return None;
+            }
+            diagnostic::closest_word(word, &vocabulary).map(str::to_string)
+        },
+        _ => None,
+    }
+}
+
+/// Renames the outputs of batch intents that would write the same file, as
+/// `a.mov` and `a.mkv` both converted to `a.mp4` would, after their input's
+/// extension: `a_mov.mp4` and `a_mkv.mp4`.
+///
+/// It is an error when two intents still share an output, as when every
+/// input is written to one named file.
+fn separate_outputs(intents: &mut [Intent]) -> Result<(), ParseError> {
+    let shared: Vec<bool> = intents.iter()
+        .map(|intent| intents.iter().filter(|other| other.output_path == intent.output_path).count() > 1)
+        .collect();
+    for (intent, _) in intents.iter_mut().zip(shared).filter(|(_, shared)| *shared) {
+        let input_extension = intent.input_path()
+            .and_then(Path::extension)
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let stem = intent.output_path.file_stem().unwrap_or_default().to_string_lossy();
+        let name = match intent.output_path.extension() {
+            Some(extension) => format!("{}_{}.{}", stem, input_extension, extension.to_string_lossy()),
+            None => format!("{}_{}", stem, input_extension),
+        };
+        intent.output_path = intent.output_path.with_file_name(name);
+    }
+
+    for (index, intent) in intents.iter().enumerate() {
+        if let Some(other) = intents[..index].iter().find(|other| other.output_path == intent.output_path) {
+            return Err(ParseError::InvalidParameter(format!(
+                "{} and {} would both be written to {}; name the output after each input instead",
+                other.input_paths[0].display(),
+                intent.input_paths[0].display(),
+                intent.output_path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Builds an output path next to `input_path` with a suffixed stem and a new extension.
+fn derive_output_path(input_path: &str, suffix: &str, extension: &str) -> Result<String, ParseError> {
+    let input_path_buf = PathBuf::from(input_path);
+    let dir = input_path_buf.parent().unwrap_or_else(|| std::path::Path::new(""));
+    let base_name = input_path_buf.file_stem()
+        .ok_or_else(|| ParseError::InvalidPath(input_path.to_string()))?;
+
+    let dir_str = dir.to_string_lossy();
+    let base_name_str = base_name.to_string_lossy();
+
+    let new_path = if dir_str.is_empty() {
+        format!("{}{}.{}", base_name_str, suffix, extension)
+    } else {
+        format!("{}/{}{}.{}", dir_str, base_name_str, suffix, extension)
+    };
+
+    Ok(new_path)
+}
+
+/// Builds the output path for a bare target format such as `.mp4`.
+///
+/// Converting to the format the input already has would name the input
+/// itself, which ffmpeg would destroy while reading it, so such outputs get a
+/// `_converted` suffix instead: `video.mp4` to `.mp4` gives `video_converted.mp4`.
+fn derive_format_output(input_path: &str, extension: &str) -> Result<String, ParseError> {
+    let output_path = derive_output_path(input_path, "", extension)?;
+    if output_path.to_lowercase() == input_path.to_lowercase() {
+        return derive_output_path(input_path, "_converted", extension);
+    }
+    Ok(output_path)
+}
+
+/// Parses a `WIDTHxHEIGHT` word such as `1280x720`.
+fn parse_size(word: &str) -> Option<(Pixels, Pixels)> {
+    let (width, height) = word.split_once('x')?;
+    Some((parse_dimension(width).ok()?, parse_dimension(height).ok()?))
+}
+
+/// Parses a single positive pixel dimension.
+pub(crate) fn parse_dimension(value: &str) -> Result<Pixels, ParseError> {
+    match value.parse::<u32>().ok().and_then(|dimension| Pixels::new(dimension).ok()) {
+        Some(pixels) => Ok(pixels),
+        None => Err(ParseError::InvalidParameter(format!(
+            "'{}' is not a valid size in pixels", value
+        ))),
+    }
+}
+
+/// Parses a frame rate in frames per second.
+pub(crate) fn parse_frame_rate(value: &str) -> Result<FrameRate, ParseError> {
+    match value.parse::<f64>() {
+        Ok(rate) => Ok(FrameRate::per_second(rate)?),
+        _ => Err(ParseError::InvalidParameter(format!(
+            "'{}' is not a valid frame rate", value
+        ))),
+    }
+}
+
+/// Maps relative size words to a size relative to the input.
+fn scale_expression(word: &str) -> Option<Resolution> {
+    match word {
+        "half" => Some(Resolution::Half),
+        "quarter" => Some(Resolution::Quarter),
+        "double" | "twice" => Some(Resolution::Double),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::tokenizer::{Tokenizer};
+    use crate::intent::params::TimeSpan;
+
+    #[test]
+    fn test_parse_convert_command() {
+        let mut tokenizer = Tokenizer::new("convert video.mp4 to video.avi");
+        let tokens = tokenizer.tokenize();
+        let mut parser = Parser::new(tokens);
+        let intent = parser.parse().unwrap();
+
+        assert_eq!(intent.operation, Operation::Convert);
+        assert_eq!(intent.input_paths, vec![PathBuf::from("video.mp4")]);
+        assert_eq!(intent.output_path, PathBuf::from("video.avi"));
+    }
+
+    #[test]
+    fn test_parse_convert_with_format() {
+        let mut tokenizer = Tokenizer::new("convert video.mp4 to .avi");
+        let tokens = tokenizer.tokenize();
+        let mut parser = Parser::new(tokens);
+        let intent = parser.parse().unwrap();
+
+        assert_eq!(intent.operation, Operation::Convert);
+        assert_eq!(intent.input_paths, vec![PathBuf::from("video.mp4")]);
+        assert_eq!(intent.output_path, PathBuf::from("video.avi"));
+    }
+
+    #[test]
+    fn test_parse_convert_to_same_format_adds_suffix() {
+        let intent = parse("convert video.mp4 to .mp4").unwrap();
+        assert_eq!(intent.output_path, PathBuf::from("video_converted.mp4"));
+
+        let intent = parse("convert clips/Video.MP4 to mp4").unwrap();
+        assert_eq!(intent.output_path, PathBuf::from("clips/Video_converted.mp4"));
+    }
+
+    #[test]
+    fn test_parse_quoted_path_with_spaces() {
+        let mut tokenizer = Tokenizer::new("convert \"My Holiday.mp4\" to .webm");
+        let tokens = tokenizer.tokenize();
+        let mut parser = Parser::new(tokens);
+        let intent = parser.parse().unwrap();
+
+        assert_eq!(intent.input_paths, vec![PathBuf::from("My Holiday.mp4")]);
+        assert_eq!(intent.output_path, PathBuf::from("My Holiday.webm"));
+    }
+
+    #[test]
+    fn test_parse_please_convert_into_an_mp4() {
+        let intent = parse("please convert my video.mp4 into an mp4").unwrap();
+        assert_eq!(intent.operation, Operation::Convert);
+        assert_eq!(intent.input_paths, vec![PathBuf::from("video.mp4")]);
+        assert_eq!(intent.output_path, PathBuf::from("video_converted.mp4"));
+    }
+
+    #[test]
+    fn test_parse_fillers_within_a_chain() {
+        let intent = parse("please trim the talk.mp4 from 0:10 to 1:00, then just turn it into webm").unwrap();
+        assert_eq!(intent.input_paths, vec![PathBuf::from("talk.mp4")]);
+        assert_eq!(intent.output_path, PathBuf::from("talk_trimmed.webm"));
+        assert!(matches!(intent.operation, Operation::Trim { .. }));
+    }
+
+    #[test]
+    fn test_parse_turn_into_gif() {
+        let intent = parse("turn clip.mov into gif").unwrap();
+        assert_eq!(intent.operation, Operation::Convert);
+        assert_eq!(intent.output_path, PathBuf::from("clip.gif"));
+        assert!(parse("turn clip.mov").is_err());
+    }
+
+    #[test]
+    fn test_parse_change_to_format_word() {
+        let intent = parse("change x.wav to mp3 format").unwrap();
+        assert_eq!(intent.operation, Operation::Convert);
+        assert_eq!(intent.input_paths, vec![PathBuf::from("x.wav")]);
+        assert_eq!(intent.output_path, PathBuf::from("x.mp3"));
+    }
+
+    #[test]
+    fn test_parse_save_as() {
+        let intent = parse("save clip.mov as .webm").unwrap();
+        assert_eq!(intent.operation, Operation::Convert);
+        assert_eq!(intent.output_path, PathBuf::from("clip.webm"));
+
+        let intent = parse("export the clip.mov to an mkv file").unwrap();
+        assert_eq!(intent.output_path, PathBuf::from("clip.mkv"));
+    }
+
+    #[test]
+    fn test_parse_format_without_dot() {
+        let intent = parse("convert clip.mov to mp3").unwrap();
+        assert_eq!(intent.operation, Operation::Convert);
+        assert_eq!(intent.output_path, PathBuf::from("clip.mp3"));
+        assert_eq!(parse("convert clip.mov to .mp3").unwrap().output_path, intent.output_path);
+    }
+
+    #[test]
+    fn test_parse_filler_word_as_file_name() {
+        let intent = parse("convert my.mp4 to an avi").unwrap();
+        assert_eq!(intent.input_paths, vec![PathBuf::from("my.mp4")]);
+        assert_eq!(intent.output_path, PathBuf::from("my.avi"));
+
+        let intent = parse("please convert the the.mp4 to avi").unwrap();
+        assert_eq!(intent.input_paths, vec![PathBuf::from("the.mp4")]);
+    }
+
+    #[test]
+    fn test_parse_make_still_makes_a_gif() {
+        assert!(matches!(parse("make a gif from clip.mp4").unwrap().operation, Operation::Gif(_)));
+        assert!(matches!(parse("make an animated gif from clip.mp4").unwrap().operation, Operation::Gif(_)));
+
+        let intent = parse("make clip.mov into an avi").unwrap();
+        assert_eq!(intent.operation, Operation::Convert);
+        assert_eq!(intent.output_path, PathBuf::from("clip.avi"));
+    }
+
+    fn parse(command: &str) -> Result<Intent, ParseError> {
+        Parser::for_command(command).parse()
+    }
+
+    #[test]
+    fn test_parse_resize_dimensions() {
+        let intent = parse("resize clip.mp4 to 1280x720").unwrap();
+
+        assert_eq!(intent.operation, Operation::Resize(Resolution::exact(1280, 720).unwrap()));
+        assert_eq!(intent.output_path, PathBuf::from("clip_resized.mp4"));
+    }
+
+    #[test]
+    fn test_parse_resize_named_height_with_output() {
+        let intent = parse("resize clip.mp4 to 720p as clip_small.mp4").unwrap();
+
+        assert_eq!(intent.output_path, PathBuf::from("clip_small.mp4"));
+        assert_eq!(intent.operation, Operation::Resize(Resolution::height(720).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_resize_relative_and_single_side() {
+        let intent = parse("resize clip.mp4 to half size").unwrap();
+        assert_eq!(intent.operation, Operation::Resize(Resolution::Half));
+
+        let intent = parse("resize clip.mp4 to width 640 keeping aspect ratio").unwrap();
+        assert_eq!(intent.operation, Operation::Resize(Resolution::width(640).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_resize_rejects_zero_size() {
+        assert!(matches!(parse("resize clip.mp4 to 0x720"), Err(ParseError::UnexpectedToken(_))));
+        assert!(matches!(parse("resize clip.mp4 to width 0"), Err(ParseError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_parse_transcode_codecs() {
+        let encoders = |intent: &Intent| match intent.operation {
+            Operation::Transcode { video, audio } => (video.map(|codec| codec.encoder), audio.map(|codec| codec.encoder)),
+            _ => panic!("not a transcode: {:?}", intent.operation),
+        };
+
+        let intent = parse("transcode movie.mkv to movie.mp4 using h265 and opus").unwrap();
+        assert_eq!(encoders(&intent), (Some("libx265"), Some("libopus")));
+
+        let intent = parse("transcode movie.mkv to movie.webm with video codec vp9").unwrap();
+        assert_eq!(encoders(&intent), (Some("libvpx-vp9"), None));
+    }
+
+    #[test]
+    fn test_parse_transcode_unknown_codec() {
+        let error = parse("transcode movie.mkv to movie.mp4 using divx").unwrap_err();
+
+        assert!(matches!(error, ParseError::UnknownCodec(_)));
+        assert!(error.to_string().contains("h264"));
+    }
+
+    #[test]
+    fn test_parse_transcode_codec_kind_mismatch() {
+        let result = parse("transcode movie.mkv to movie.mp4 with audio codec h264");
+        assert!(matches!(result, Err(ParseError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_parse_transcode_misspelled_codec_clause() {
+        let error = parse("transcode movie.mkv to movie.mp4 usng h265").unwrap_err();
+        assert!(matches!(error, ParseError::UnexpectedToken(ref token) if token.ends_with("usng")), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_trim_range() {
+        let intent = parse("trim talk.mp4 from 1:30 to 2:45 as excerpt.mp4").unwrap();
+
+        assert_eq!(intent.operation, Operation::Trim {
+            range: TimeRange::between(Some(90.0), Some(165.0)).unwrap(),
+            mode: TrimMode::Copy,
+        });
+        assert_eq!(intent.input_paths, vec![PathBuf::from("talk.mp4")]);
+        assert_eq!(intent.output_path, PathBuf::from("excerpt.mp4"));
+    }
+
+    #[test]
+    fn test_parse_trim_edges() {
+        let intent = parse("cut the first 10 seconds of intro.mov").unwrap();
+        assert_eq!(intent.output_path, PathBuf::from("intro_trimmed.mov"));
+        assert_eq!(intent.operation, Operation::Trim {
+            range: TimeRange::between(Some(10.0), None).unwrap(),
+            mode: TrimMode::Copy,
+        });
+
+        let intent = parse("keep the last 30s of x.mp4").unwrap();
+        assert_eq!(intent.operation, Operation::Trim { range: TimeRange::last(30.0).unwrap(), mode: TrimMode::Copy });
+
+        let intent = parse("keep the first 2 minutes of x.mp4 precisely").unwrap();
+        assert_eq!(intent.operation, Operation::Trim {
+            range: TimeRange::starting_for(None, 120.0).unwrap(),
+            mode: TrimMode::Accurate,
+        });
+    }
+
+    #[test]
+    fn test_parse_trim_rejects_reversed_range() {
+        let error = parse("trim talk.mp4 from 2:00 to 1:00").unwrap_err();
+        assert!(matches!(error, ParseError::InvalidParameter(_)));
+        assert!(error.to_string().contains("end time 00:01:00 is not after start time 00:02:00"));
+    }
+
+    #[test]
+    fn test_parse_trim_misspelled_mode() {
+        let error = parse("trim talk.mp4 from 1:30 to 2:45 precisly").unwrap_err();
+        assert!(matches!(error, ParseError::UnexpectedToken(ref token) if token.ends_with("precisly")), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_concat_list() {
+        let intent = parse("join part1.mp4, part2.mp4 and part3.mp4 into full.mp4").unwrap();
+
+        assert_eq!(intent.operation, Operation::Concat(ConcatMethod::default()));
+        assert_eq!(intent.input_paths, vec![
+            PathBuf::from("part1.mp4"),
+            PathBuf::from("part2.mp4"),
+            PathBuf::from("part3.mp4"),
+        ]);
+        assert_eq!(intent.output_path, PathBuf::from("full.mp4"));
+
+        let intent = parse("merge a.mp4, b.mp4, and c.mp4").unwrap();
+        assert_eq!(intent.input_paths.len(), 3);
+        assert_eq!(intent.output_path, PathBuf::from("a_joined.mp4"));
+    }
+
+    #[test]
+    fn test_parse_concat_needs_two_inputs() {
+        assert!(matches!(parse("join part1.mp4 into full.mp4"), Err(ParseError::MissingToken(_))));
+    }
+
+    #[test]
+    fn test_parse_gif() {
+        let intent = parse("make a gif from clip.mp4 from 0:05 for 3 seconds at 15 fps 480 wide").unwrap();
+
+        assert_eq!(intent.operation, Operation::Gif(GifOptions {
+            range: Some(TimeRange::starting_for(Some(5.0), 3.0).unwrap()),
+            fps: FrameRate::per_second(15.0).unwrap(),
+            width: Pixels::new(480).unwrap(),
+        }));
+        assert_eq!(intent.input_paths, vec![PathBuf::from("clip.mp4")]);
+        assert_eq!(intent.output_path, PathBuf::from("clip.gif"));
+    }
+
+    #[test]
+    fn test_parse_gif_with_output_and_compact_fps() {
+        let intent = parse("create an animated gif of clip.mp4 at 12fps as loop.gif").unwrap();
+
+        assert_eq!(intent.output_path, PathBuf::from("loop.gif"));
+        assert_eq!(intent.operation, Operation::Gif(GifOptions {
+            fps: FrameRate::per_second(12.0).unwrap(),
+            ..GifOptions::default()
+        }));
+    }
+
+    #[test]
+    fn test_parse_thumbnail() {
+        let intent = parse("grab a frame from movie.mp4 at 00:01:23 as poster.jpg").unwrap();
+        assert_eq!(intent.operation, Operation::Thumbnail { time: Some(TimePoint::new(83.0).unwrap()) });
+        assert_eq!(intent.output_path, PathBuf::from("poster.jpg"));
+
+        let intent = parse("make a thumbnail of video.mp4").unwrap();
+        assert_eq!(intent.operation, Operation::Thumbnail { time: None });
+        assert_eq!(intent.output_path, PathBuf::from("video_thumbnail.jpg"));
+    }
+
+    #[test]
+    fn test_parse_extract_frames() {
+        let intent = parse("extract one frame every 10 seconds from lecture.mp4 into frames/").unwrap();
+
+        assert_eq!(intent.operation, Operation::ExtractFrames(FrameRate::every(10.0).unwrap()));
+        assert_eq!(intent.output_path, PathBuf::from("frames/lecture_%04d.jpg"));
+
+        let intent = parse("extract 2 frames per second from clip.mp4 as png").unwrap();
+        assert_eq!(intent.output_path, PathBuf::from("clip_%04d.png"));
+        assert_eq!(intent.operation, Operation::ExtractFrames(FrameRate::per_second(2.0).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_extract_frames_rejects_zero_frames() {
+        for command in ["extract 0 frames every 10 seconds from clip.mp4", "extract 0 frames per second from clip.mp4"] {
+            let error = parse(command).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "Invalid parameter: 0 frames is not a valid number of frames; ask for at least one",
+                "{}",
+                command
+            );
+        }
+
+        let diagnostic = diagnose("extract 0 frames every 10 seconds from clip.mp4");
+        assert_eq!(diagnostic.span, Some(8..9));
+    }
+
+    #[test]
+    fn test_parse_extract_audio() {
+        let intent = parse("extract audio from talk.mp4 as talk.mp3 at 192 kbps").unwrap();
+        assert_eq!(intent.operation, Operation::ExtractAudio(Quality::Bitrate(Bitrate::from_kbps(192).unwrap())));
+        assert_eq!(intent.output_path, PathBuf::from("talk.mp3"));
+
+        let intent = parse("extract audio from talk.mp4 as lossless flac").unwrap();
+        assert_eq!(intent.output_path, PathBuf::from("talk.flac"));
+
+        let intent = parse("extract audio from talk.mp4 without re-encoding").unwrap();
+        assert_eq!(intent.output_path, PathBuf::from("talk.mka"));
+        assert_eq!(intent.operation, Operation::ExtractAudio(Quality::Copy { source: None }));
+
+        let intent = parse("extract video.mp4 to audio.wav").unwrap();
+        assert_eq!(intent.output_path, PathBuf::from("audio.wav"));
+    }
+
+    #[test]
+    fn test_parse_extract_audio_rejects_bad_bitrate() {
+        let result = parse("extract audio from talk.mp4 at 5000 kbps");
+        assert!(matches!(result, Err(ParseError::InvalidParameter(_))));
+
+        let result = parse("extract audio from talk.mp4 at 192 kbps without re-encoding");
+        assert!(matches!(result, Err(ParseError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_parse_extract_audio_misspelled_options() {
+        for (command, word) in [
+            ("extract audio from talk.mp4 withot re-encoding", "withot"),
+            ("extract audio from talk.mp4 at 192 kbsp", "kbsp"),
+        ] {
+            let error = parse(command).unwrap_err();
+            assert!(matches!(error, ParseError::UnexpectedToken(ref token) if token.ends_with(word)), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_parse_inspect() {
+        let intent = parse("inspect video.mp4").unwrap();
+        assert_eq!(intent.operation, Operation::Inspect);
+        assert_eq!(intent.input_paths, vec![PathBuf::from("video.mp4")]);
+
+        let intent = parse("what is in movie.mkv").unwrap();
+        assert_eq!(intent.operation, Operation::Inspect);
+        assert_eq!(intent.input_paths, vec![PathBuf::from("movie.mkv")]);
+    }
+
+    #[test]
+    fn test_parse_records_source_words() {
+        let intent = parse("resize clip.mp4 to 1280 wide").unwrap();
+        assert_eq!(intent.source("operation"), Some("resize"));
+        assert_eq!(intent.source("input"), Some("clip.mp4"));
+        assert_eq!(intent.source("size"), Some("to 1280 wide"));
+        assert_eq!(intent.source("output"), None);
+
+        let intent = parse("trim talk.mp4 from 1:30 to 2:45 as excerpt.mp4").unwrap();
+        assert_eq!(intent.source("start"), Some("from 1:30"));
+        assert_eq!(intent.source("end"), Some("to 2:45"));
+        assert_eq!(intent.source("output"), Some("as excerpt.mp4"));
+    }
+
+    #[test]
+    fn test_parse_records_source_words_as_written() {
+        let intent = parse("trim talk.mp4 from 5 seconds").unwrap();
+        assert_eq!(intent.source("start"), Some("from 5 seconds"));
+
+        let intent = parse("keep the last 30s of x.mp4").unwrap();
+        assert_eq!(intent.source("from_end"), Some("the last 30s"));
+
+        let intent = parse("Convert  \"my clip.MOV\" to .webm").unwrap();
+        assert_eq!(intent.source("input"), Some("\"my clip.MOV\""));
+
+        let intent = Parser::new(Tokenizer::new("trim talk.mp4 from 5 seconds").tokenize()).parse().unwrap();
+        assert_eq!(intent.source("start"), Some("from 00:00:05"));
+    }
+
+    #[test]
+    fn test_every_verb_starts_an_operation() {
+        for (word, _) in VERBS {
+            let mut parser = Parser::new(vec![Token::Word(word.to_string())]);
+            if let Err(ParseError::UnexpectedToken(message)) = parser.parse_operation() {
+                panic!("{}: {}", word, message);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_chain_into_pipeline() {
+        let intent = parse("trim interview.mp4 from 0:10 to 5:00, resize it to 720p and convert to webm").unwrap();
+        assert_eq!(
+            intent.operation,
+            Operation::Pipeline(vec![
+                Operation::Trim { range: TimeRange::between(Some(10.0), Some(300.0)).unwrap(), mode: TrimMode::Copy },
+                Operation::Resize(Resolution::height(720).unwrap()),
+            ])
+        );
+        assert_eq!(intent.input_paths, vec![PathBuf::from("interview.mp4")]);
+        assert_eq!(intent.output_path, PathBuf::from("interview_trimmed_resized.webm"));
+
+        let intent = parse("resize clip.mp4 to half size and then make a thumbnail of the result as poster.png").unwrap();
+        assert_eq!(
+            intent.operation,
+            Operation::Pipeline(vec![Operation::Resize(Resolution::Half), Operation::Thumbnail { time: None }])
+        );
+        assert_eq!(intent.output_path, PathBuf::from("poster.png"));
+
+        let intent = parse("trim clip.mp4 from 1:00 then make a gif from 0:05 for 3 seconds").unwrap();
+        assert!(matches!(&intent.operation, Operation::Pipeline(steps)
+            if matches!(steps[1], Operation::Gif(GifOptions { range: Some(range), .. })
+                if matches!(range.span(), TimeSpan::For { .. }))));
+    }
+
+    #[test]
+    fn test_parse_chain_collapses_to_single_operation() {
+        let intent = parse("resize clip.mp4 to 720p and convert it to webm").unwrap();
+        assert_eq!(intent.operation, Operation::Resize(Resolution::height(720).unwrap()));
+        assert_eq!(intent.output_path, PathBuf::from("clip_resized.webm"));
+
+        let intent = parse("transcode a.mkv using h265 and opus, then convert to mp4").unwrap();
+        assert!(matches!(intent.operation, Operation::Transcode { video: Some(_), audio: Some(_) }));
+        assert_eq!(intent.output_path, PathBuf::from("a_transcoded.mp4"));
+
+        let intent = parse("resize clip.mp4 to 720p and keep the aspect ratio").unwrap();
+        assert_eq!(intent.operation, Operation::Resize(Resolution::height(720).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_chain_errors() {
+        let error = parse("trim a.mp4 from 0:10 as b.mp4 and then resize it to 720p").unwrap_err();
+        assert!(error.to_string().contains("b.mp4 would never be written"));
+
+        let error = parse("trim a.mp4 from 0:10 and then resize b.mp4 to 720p").unwrap_err();
+        assert!(error.to_string().contains("say 'it' instead of b.mp4"));
+
+        assert!(parse("join a.mp4 and b.mp4 and then resize it to 720p").is_err());
+        assert_eq!(parse("join a.mp4 and b.mp4").unwrap().input_paths.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_chain_misspelled_step() {
+        for command in [
+            "trim interview.mp4 from 0:10 to 5:00, then rezise it to 720p",
+            "trim interview.mp4 from 0:10 to 5:00 and rezise it to 720p",
+        ] {
+            let error = parse(command).unwrap_err();
+            assert_eq!(error.to_string(), "Unexpected token: Unknown operation: rezise", "{}", command);
+        }
+
+        let diagnostic = diagnose("trim interview.mp4 from 0:10 to 5:00, then rezise it to 720p");
+        assert_eq!(diagnostic.span, Some(43..49));
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("resize"));
+    }
+
+    fn parse_all(command: &str) -> Result<Vec<Intent>, ParseError> {
+        Parser::for_command(command).parse_all()
+    }
+
+    fn media_dir(names: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for name in names {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_parse_all_expands_glob_into_output_directory() {
+        let dir = media_dir(&["a.mov", "b.mov", "notes.txt", "c.mp4"]);
+        let raw = dir.path().display();
+        let intents = parse_all(&format!("convert all *.mov in \"{}\" to .mp4 into ./out", raw)).unwrap();
+
+        assert_eq!(intents.len(), 2);
+        assert_eq!(intents[0].input_paths, vec![dir.path().join("a.mov")]);
+        assert_eq!(intents[0].output_path, PathBuf::from("./out/a.mp4"));
+        assert_eq!(intents[1].input_paths, vec![dir.path().join("b.mov")]);
+        assert_eq!(intents[1].output_path, PathBuf::from("./out/b.mp4"));
+    }
+
+    #[test]
+    fn test_parse_all_separates_outputs_of_same_named_inputs() {
+        let dir = media_dir(&["a.mov", "a.mkv", "b.mov"]);
+        let raw = dir.path();
+        let intents = parse_all(&format!("convert \"{}/*\" to .mp4", raw.display())).unwrap();
+        let outputs: Vec<_> = intents.iter().map(|intent| intent.output_path.clone()).collect();
+        assert_eq!(outputs, vec![raw.join("a_mkv.mp4"), raw.join("a_mov.mp4"), raw.join("b.mp4")]);
+
+        let error = parse_all(&format!("convert all *.mov in \"{}\" to all.mp4", raw.display())).unwrap_err();
+        assert!(error.to_string().contains("would both be written to all_mov.mp4"), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_all_every_format_word() {
+        let dir = media_dir(&["ep1.wav", "ep2.WAV", "cover.jpg"]);
+        let intents = parse_all(&format!("convert every wav in \"{}/\" to mp3", dir.path().display())).unwrap();
+
+        let outputs: Vec<_> = intents.iter().map(|intent| intent.output_path.clone()).collect();
+        assert_eq!(outputs, vec![dir.path().join("ep1.mp3"), dir.path().join("ep2.mp3")]);
+    }
+
+    #[test]
+    fn test_parse_all_join_takes_every_match() {
+        let dir = media_dir(&["part1.mp4", "part2.mp4", "part3.mp4"]);
+        let intents = parse_all(&format!("join all mp4 files in \"{}\" into full.mp4", dir.path().display())).unwrap();
+
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].input_paths.len(), 3);
+        assert_eq!(intents[0].output_path, PathBuf::from("full.mp4"));
+    }
+
+    #[test]
+    fn test_parse_all_without_batch_or_matches() {
+        let intents = parse_all("convert video.mp4 to mp3").unwrap();
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].output_path, PathBuf::from("video.mp3"));
+
+        let dir = media_dir(&["a.mp4"]);
+        let result = parse_all(&format!("convert all *.mov in \"{}\" to .mp4", dir.path().display()));
+        assert!(matches!(result, Err(ParseError::NoMatchingFiles(_))));
+    }
+
+    fn diagnose(command: &str) -> Diagnostic {
+        let mut parser = Parser::with_spans(Tokenizer::new(command).tokenize_with_spans());
+        let error = parser.parse_all().unwrap_err();
+        parser.diagnose(error)
+    }
+
+    #[test]
+    fn test_diagnose_points_at_the_offending_word() {
+        let diagnostic = diagnose("covert video.mp4 to video.avi");
+        assert_eq!(diagnostic.span, Some(0..6));
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("convert"));
+
+        let diagnostic = diagnose("convert video.mp4 to video.mpp4");
+        assert_eq!(diagnostic.span, Some(21..31));
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("video.mp4"));
+
+        let diagnostic = diagnose("convert video.mp4 to .mpp4");
+        assert_eq!(diagnostic.suggestion.as_deref(), Some(".mp4"));
+
+        let diagnostic = diagnose("transcode movie.mkv to movie.mp4 using h246");
+        assert_eq!(diagnostic.span, Some(39..43));
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("h264"));
+
+        let diagnostic = diagnose("resize clip.mp4 ot 720p");
+        assert_eq!(diagnostic.span, Some(16..18));
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("to"));
+    }
+
+    #[test]
+    fn test_diagnose_trailing_words() {
+        let diagnostic = diagnose("transcode movie.mkv to movie.mp4 usng h265");
+        assert!(matches!(diagnostic.error, ParseError::UnexpectedToken(_)));
+        assert_eq!(diagnostic.span, Some(33..37));
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("using"));
+
+        let diagnostic = diagnose("extract audio from talk.mp4 withot re-encoding");
+        assert_eq!(diagnostic.span, Some(28..34));
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("without"));
+
+        let diagnostic = diagnose("make a gif from clip.mp4 at 15 fsp");
+        assert_eq!(diagnostic.span, Some(31..34));
+        assert_eq!(diagnostic.suggestion.as_deref(), Some("fps"));
+
+        let diagnostic = diagnose("convert clip.mp4 to .webm resize it to 720p");
+        assert!(matches!(diagnostic.error, ParseError::UnexpectedToken(_)));
+        assert_eq!(diagnostic.span, Some(26..32));
+    }
+
+    #[test]
+    fn test_diagnose_without_suggestion_at_end_of_input() {
+        let diagnostic = diagnose("trim talk.mp4 from");
+        assert!(matches!(diagnostic.error, ParseError::MissingToken(_)));
+        assert_eq!(diagnostic.span, Some(18..19));
+        assert_eq!(diagnostic.suggestion, None);
+
+        let diagnostic = diagnose("banana video.mp4");
+        assert_eq!(diagnostic.span, Some(0..6));
+        assert_eq!(diagnostic.suggestion, None);
+    }
+
+    #[test]
+    fn test_diagnose_shows_an_example_of_the_operation() {
+        assert_eq!(diagnose("trim talk.mp4 form 1:00").example, "trim talk.mp4 from 1:30 to 2:45");
+        assert_eq!(diagnose("extract audio from talk.mp4 as mp9").example, "extract audio from talk.mp4 as mp3");
+        assert_eq!(diagnose("covert video.mp4").example, "convert video.mp4 to video.avi");
+        assert_eq!(
+            diagnose("join a.mp4 and b.mp4 into c.mp4, then resize it to 720p").example,
+            "trim interview.mp4 from 0:10 to 5:00, then resize it to 720p"
+        );
+    }
+}
@@ -1,4 +1,10 @@
 
+use crate::utils::time;
+use std::fmt;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::CharIndices;
+
 /// Token types for the FF CLI tool.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
@@ -10,17 +16,40 @@ pub enum Token {
     Format(String),
     /// A numeric value
     Number(f64),
+    /// A timestamp or duration in seconds (e.g., 1:30, 90s, 2 minutes)
+    Time(f64),
+    /// A comma separating list items
+    Comma,
     /// An unknown token type
     Unknown(String),
 }
 
+impl fmt::Display for Token {
+    /// Writes the token back as words, quoting paths that contain spaces.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(text) | Token::Format(text) | Token::Unknown(text) => write!(f, "{}", text),
+            Token::Path(path) if path.contains(char::is_whitespace) => write!(f, "\"{}\"", path),
+            Token::Path(path) => write!(f, "{}", path),
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Time(seconds) => write!(f, "{}", time::format_timestamp(*seconds)),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
 /// Tokenizer for converting plain English commands into tokens.
-pub struct Tokenizer {
-    text: String,
-    position: usize,
+///
+/// The text is read once, front to back, through a peekable iterator of
+/// characters and their byte offsets. Offsets always fall on character
+/// boundaries, so slicing the text is safe for any UTF-8 input, and looking
+/// ahead or backtracking only copies the iterator.
+pub struct Tokenizer<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
 }
 
-impl Tokenizer {
+impl<'a> Tokenizer<'a> {
     /// Creates a new tokenizer for the given text.
     ///
     /// # Arguments
@@ -34,10 +63,10 @@ impl Tokenizer {
     /// let mut tokenizer = Tokenizer::new("convert video.mp4 to video.avi");
     /// let tokens = tokenizer.tokenize();
     /// ```
-    pub fn new(text: &str) -> Self {
+    pub fn new(text: &'a str) -> Self {
         Self {
-            text: text.to_string(),
-            position: 0,
+            text,
+            chars: text.char_indices().peekable(),
         }
     }
 
@@ -47,57 +76,114 @@ impl Tokenizer {
     ///
     /// A vector of `Token` enums representing the parsed tokens.
     pub fn tokenize(&mut self) -> Vec<Token> {
+        self.tokenize_with_spans().into_iter().map(|(token, _)| token).collect()
+    }
+
+    /// Tokenizes the input text, keeping where each token came from.
+    ///
+    /// # Returns
+    ///
+    /// Each token with the byte range of the text it was read from, so that
+    /// errors can point at the words that caused them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ffrs::Tokenizer;
+    /// let tokens = Tokenizer::new("covert a.mp4").tokenize_with_spans();
+    /// assert_eq!(tokens[1].1, 7..12);
+    /// ```
+    pub fn tokenize_with_spans(&mut self) -> Vec<(Token, Range<usize>)> {
         let mut tokens = Vec::new();
 
-        while self.position < self.text.len() {
-            if let Some(token) = self.next_token() {
-                tokens.push(token);
-            } else {
-                self.position += 1;
-            }
+        loop {
+            self.skip_whitespace();
+            let start = self.position();
+            let Some(token) = self.next_token() else {
+                break;
+            };
+            tokens.push((token, start..self.position()));
         }
 
         tokens
     }
 
     fn next_token(&mut self) -> Option<Token> {
-        while self.position < self.text.len() &&
-              self.text.chars().nth(self.position).unwrap().is_whitespace() {
-            self.position += 1;
+        let ch = self.peek()?;
+
+        if ch == ',' {
+            self.advance();
+            return Some(Token::Comma);
         }
 
-        if self.position >= self.text.len() {
-            return None;
+        if ch == '"' || ch == '\'' {
+            return Some(self.tokenize_quoted(ch));
         }
 
-        if self.text.chars().nth(self.position).unwrap() == '.' {
+        if ch == '.' && self.peek_second().is_some_and(|c| c == '/' || c == '.') {
+            return Some(self.tokenize_path());
+        }
+
+        if ch == '.' {
             return Some(self.tokenize_format());
         }
 
-        if self.text.chars().nth(self.position).unwrap() == '/' {
+        if ch == '/' {
             return Some(self.tokenize_path());
         }
 
-        if self.text.chars().nth(self.position).unwrap().is_numeric() {
+        if ch.is_ascii_digit() {
             return Some(self.tokenize_number());
         }
 
-        Some(self.tokenize_word())
+        if is_word_char(ch) || ch == '\\' {
+            return Some(self.tokenize_word());
+        }
+
+        self.advance();
+        Some(Token::Unknown(ch.to_string()))
+    }
+
+    /// Returns the byte offset of the next character, or the text's length
+    /// once everything has been read.
+    fn position(&mut self) -> usize {
+        self.chars.peek().map_or(self.text.len(), |&(offset, _)| offset)
+    }
+
+    /// Returns the character at the current position without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, ch)| ch)
+    }
+
+    /// Returns the character after the current one without consuming anything.
+    fn peek_second(&self) -> Option<char> {
+        self.chars.clone().nth(1).map(|(_, ch)| ch)
+    }
+
+    /// Consumes and returns the character at the current position.
+    fn advance(&mut self) -> Option<char> {
+        self.chars.next().map(|(_, ch)| ch)
+    }
+
+    /// Consumes characters while they match the predicate.
+    fn advance_while(&mut self, predicate: impl Fn(char) -> bool) {
+        while self.chars.next_if(|&(_, ch)| predicate(ch)).is_some() {}
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.advance_while(char::is_whitespace);
+    }
+
+    /// Returns the text from `start` up to the current position.
+    fn slice_from(&mut self, start: usize) -> &'a str {
+        let end = self.position();
+        &self.text[start..end]
     }
 
     fn tokenize_word(&mut self) -> Token {
-        let start = self.position;
-        while self.position < self.text.len() {
-            let ch = self.text.chars().nth(self.position).unwrap();
-            if ch.is_alphanumeric() || ch == '_' || ch == '.' {
-                self.position += 1;
-            } else {
-                break;
-            }
-        }
-        let word = self.text[start..self.position].to_string();
+        let (word, escaped) = self.scan_word();
 
-        if word.contains('.') && !word.starts_with('.') {
+        if escaped || word.contains(['/', '*']) || (word.contains('.') && !word.starts_with('.')) {
             Token::Path(word)
         } else {
             Token::Word(word.to_lowercase())
@@ -105,69 +191,167 @@ impl Tokenizer {
     }
 
     fn tokenize_path(&mut self) -> Token {
-        let start = self.position;
-        while self.position < self.text.len() {
-            let ch = self.text.chars().nth(self.position).unwrap();
-            if ch.is_alphanumeric() || ch == '/' || ch == '_' || ch == '-' || ch == '~' {
-                self.position += 1;
-            } else if ch == '.' && self.position + 1 < self.text.len() {
-                let next_chars = &self.text[self.position + 1..];
-                if next_chars.chars().next().map_or(false, |c| c.is_alphanumeric()) {
-                    self.position += 1;
+        let (path, _) = self.scan_word();
+        Token::Path(path)
+    }
+
+    /// Reads a run of word characters, resolving backslash escapes.
+    ///
+    /// A `.` is only kept when another word character follows it, so that a
+    /// sentence-ending full stop is not glued onto a file name. Returns the
+    /// text together with whether any escape was resolved.
+    fn scan_word(&mut self) -> (String, bool) {
+        let mut word = String::new();
+        let mut escaped = false;
+
+        while let Some(ch) = self.peek() {
+            if ch == '\\' {
+                self.advance();
+                if let Some(next) = self.advance() {
+                    word.push(next);
+                    escaped = true;
+                }
+            } else if ch == '.' {
+                if self.peek_second().is_some_and(|c| is_word_char(c) || c == '\\') {
+                    self.advance();
+                    word.push(ch);
                 } else {
                     break;
                 }
+            } else if is_word_char(ch) {
+                self.advance();
+                word.push(ch);
             } else {
                 break;
             }
         }
-        let path = self.text[start..self.position].to_string();
-        Token::Path(path)
+
+        (word, escaped)
     }
 
-    fn tokenize_format(&mut self) -> Token {
-        let start = self.position;
-        if self.text.chars().nth(self.position).unwrap() == '.' {
-            self.position += 1;
-        }
+    /// Reads a single- or double-quoted string as a path.
+    ///
+    /// Inside double quotes a backslash escapes the next character; single
+    /// quotes are taken literally. An unterminated quote runs to the end of
+    /// the input.
+    fn tokenize_quoted(&mut self, quote: char) -> Token {
+        self.advance();
 
-        while self.position < self.text.len() {
-            let ch = self.text.chars().nth(self.position).unwrap();
-            if ch.is_alphanumeric() {
-                self.position += 1;
-            } else {
+        let mut path = String::new();
+        while let Some(ch) = self.advance() {
+            if ch == quote {
                 break;
             }
+            if ch == '\\' && quote == '"' {
+                if let Some(next) = self.advance() {
+                    path.push(next);
+                }
+            } else {
+                path.push(ch);
+            }
         }
-        let format = self.text[start..self.position].to_string();
-        Token::Format(format.to_lowercase())
+
+        Token::Path(path)
     }
 
+    fn tokenize_format(&mut self) -> Token {
+        let start = self.position();
+        self.advance();
+        self.advance_while(char::is_alphanumeric);
+
+        Token::Format(self.slice_from(start).to_lowercase())
+    }
+
+    /// Reads a number, a timestamp or a duration.
+    ///
+    /// `1:30` and `00:01:23.5` become timestamps, and a unit directly after
+    /// the digits (`90s`) or after a space (`2 minutes`) makes a duration.
+    /// Other letters following the digits directly make the whole run a word
+    /// (`720p`, `1280x720`, `2024clip.mp4`).
     fn tokenize_number(&mut self) -> Token {
-        let start = self.position;
+        let checkpoint = self.chars.clone();
+        let start = self.position();
         let mut has_decimal = false;
-        
-        while self.position < self.text.len() {
-            let ch = self.text.chars().nth(self.position).unwrap();
-            if ch.is_numeric() {
-                self.position += 1;
+        let mut has_colon = false;
+
+        while let Some(ch) = self.peek() {
+            if ch.is_ascii_digit() {
+                self.advance();
             } else if ch == '.' && !has_decimal {
                 has_decimal = true;
-                self.position += 1;
+                self.advance();
+            } else if ch == ':' && self.peek_second().is_some_and(|c| c.is_ascii_digit()) {
+                has_colon = true;
+                has_decimal = false;
+                self.advance();
             } else {
                 break;
             }
         }
-        
-        let number_str = self.text[start..self.position].to_string();
-        if let Ok(number) = number_str.parse::<f64>() {
-            Token::Number(number)
-        } else {
-            Token::Unknown(number_str)
+
+        let number_str = self.slice_from(start);
+
+        if has_colon {
+            return match time::parse_timestamp(number_str) {
+                Some(seconds) => Token::Time(seconds),
+                None => Token::Unknown(number_str.to_string()),
+            };
+        }
+
+        if self.peek().is_some_and(|c| c.is_alphabetic() || c == '_' || (!c.is_ascii() && is_word_char(c))) {
+            if let (Ok(number), Some(unit)) = (number_str.parse::<f64>(), self.scan_unit(false)) {
+                return Token::Time(number * unit);
+            }
+            self.chars = checkpoint;
+            return self.tokenize_word();
+        }
+
+        let Ok(number) = number_str.parse::<f64>() else {
+            return Token::Unknown(number_str.to_string());
+        };
+
+        if let Some(unit) = self.scan_unit(true) {
+            return Token::Time(number * unit);
+        }
+
+        Token::Number(number)
+    }
+
+    /// Consumes a time unit word and returns its length in seconds.
+    ///
+    /// With `spaced`, leading whitespace is skipped and single-letter units
+    /// are refused so that `5 m` is not mistaken for minutes. Nothing is
+    /// consumed unless a complete unit word is found.
+    fn scan_unit(&mut self, spaced: bool) -> Option<f64> {
+        let checkpoint = self.chars.clone();
+        if spaced {
+            self.skip_whitespace();
+        }
+
+        let unit_start = self.position();
+        self.advance_while(char::is_alphabetic);
+        let unit = self.slice_from(unit_start).to_lowercase();
+
+        let complete = !self.peek().is_some_and(|c| is_word_char(c) || c == '.');
+        match time::unit_seconds(&unit) {
+            Some(seconds) if complete && !(spaced && unit.len() == 1) => Some(seconds),
+            _ => {
+                self.chars = checkpoint;
+                None
+            },
         }
     }
 }
 
+/// Returns whether the character can appear in an unquoted word or path.
+///
+/// `*` is included so that glob patterns such as `*.mov` stay one path, and
+/// any non-ASCII character other than whitespace is included so that names
+/// with emoji or combining accents stay whole.
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '-' | '/' | '~' | '%' | '*') || (!ch.is_ascii() && !ch.is_whitespace())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,4 +381,164 @@ mod tests {
             Token::Format(".avi".to_string()),
         ]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_tokenize_quoted_paths() {
+        let mut tokenizer = Tokenizer::new("convert \"My Holiday.mp4\" to 'Our Trip.webm'");
+        let tokens = tokenizer.tokenize();
+
+        assert_eq!(tokens, vec![
+            Token::Word("convert".to_string()),
+            Token::Path("My Holiday.mp4".to_string()),
+            Token::Word("to".to_string()),
+            Token::Path("Our Trip.webm".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_tokenize_escaped_spaces() {
+        let mut tokenizer = Tokenizer::new("convert My\\ Holiday.mp4 to .webm");
+        let tokens = tokenizer.tokenize();
+
+        assert_eq!(tokens, vec![
+            Token::Word("convert".to_string()),
+            Token::Path("My Holiday.mp4".to_string()),
+            Token::Word("to".to_string()),
+            Token::Format(".webm".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_tokenize_escaped_quote_inside_double_quotes() {
+        let mut tokenizer = Tokenizer::new("\"say \\\"hi\\\".mp4\"");
+        assert_eq!(tokenizer.tokenize(), vec![Token::Path("say \"hi\".mp4".to_string())]);
+    }
+
+    #[test]
+    fn test_tokenize_unicode_paths() {
+        let mut tokenizer = Tokenizer::new("convert \"vidéo d'été.mp4\" to 影片.avi");
+        let tokens = tokenizer.tokenize();
+
+        assert_eq!(tokens, vec![
+            Token::Word("convert".to_string()),
+            Token::Path("vidéo d'été.mp4".to_string()),
+            Token::Word("to".to_string()),
+            Token::Path("影片.avi".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_tokenize_number_with_suffix_as_word() {
+        let mut tokenizer = Tokenizer::new("to 1280x720 720p 2024clip.mp4 1.5");
+        let tokens = tokenizer.tokenize();
+
+        assert_eq!(tokens, vec![
+            Token::Word("to".to_string()),
+            Token::Word("1280x720".to_string()),
+            Token::Word("720p".to_string()),
+            Token::Path("2024clip.mp4".to_string()),
+            Token::Number(1.5),
+        ]);
+    }
+
+    #[test]
+    fn test_tokenize_times_and_durations() {
+        let mut tokenizer = Tokenizer::new("from 1:30 to 00:02:45.5 for 90s or 2 minutes but 5 m");
+        let tokens = tokenizer.tokenize();
+
+        assert_eq!(tokens, vec![
+            Token::Word("from".to_string()),
+            Token::Time(90.0),
+            Token::Word("to".to_string()),
+            Token::Time(165.5),
+            Token::Word("for".to_string()),
+            Token::Time(90.0),
+            Token::Word("or".to_string()),
+            Token::Time(120.0),
+            Token::Word("but".to_string()),
+            Token::Number(5.0),
+            Token::Word("m".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_tokenize_comma_separated_list() {
+        let mut tokenizer = Tokenizer::new("part1.mp4, part2.mp4");
+        let tokens = tokenizer.tokenize();
+
+        assert_eq!(tokens, vec![
+            Token::Path("part1.mp4".to_string()),
+            Token::Comma,
+            Token::Path("part2.mp4".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_tokenize_with_spans() {
+        let mut tokenizer = Tokenizer::new("trim  \"My Clip.mp4\" from 1:30,");
+        let spans: Vec<_> = tokenizer.tokenize_with_spans().into_iter().map(|(_, span)| span).collect();
+
+        assert_eq!(spans, vec![0..4, 6..19, 20..24, 25..29, 29..30]);
+    }
+
+    #[test]
+    fn test_tokenize_accented_names() {
+        let mut tokenizer = Tokenizer::new("convert vidéo.mp4 to Été\\ 2024.avi");
+        let tokens = tokenizer.tokenize_with_spans();
+
+        assert_eq!(tokens, vec![
+            (Token::Word("convert".to_string()), 0..7),
+            (Token::Path("vidéo.mp4".to_string()), 8..18),
+            (Token::Word("to".to_string()), 19..21),
+            (Token::Path("Été 2024.avi".to_string()), 22..37),
+        ]);
+
+        // A decomposed accent is a combining mark rather than a letter.
+        let mut tokenizer = Tokenizer::new("trim vide\u{301}o.mp4 from 5s");
+        assert_eq!(tokenizer.tokenize()[1], Token::Path("vide\u{301}o.mp4".to_string()));
+    }
+
+    #[test]
+    fn test_tokenize_cjk_names() {
+        let mut tokenizer = Tokenizer::new("resize 東京の夜.mkv to 720p, 影片");
+        let tokens = tokenizer.tokenize_with_spans();
+
+        assert_eq!(tokens, vec![
+            (Token::Word("resize".to_string()), 0..6),
+            (Token::Path("東京の夜.mkv".to_string()), 7..23),
+            (Token::Word("to".to_string()), 24..26),
+            (Token::Word("720p".to_string()), 27..31),
+            (Token::Comma, 31..32),
+            (Token::Word("影片".to_string()), 33..39),
+        ]);
+    }
+
+    #[test]
+    fn test_tokenize_emoji_names() {
+        let mut tokenizer = Tokenizer::new("convert 🎬clip.mp4 to 2024🎉.gif, \"👍 ok.mov\"");
+        let tokens = tokenizer.tokenize_with_spans();
+
+        assert_eq!(tokens, vec![
+            (Token::Word("convert".to_string()), 0..7),
+            (Token::Path("🎬clip.mp4".to_string()), 8..20),
+            (Token::Word("to".to_string()), 21..23),
+            (Token::Path("2024🎉.gif".to_string()), 24..36),
+            (Token::Comma, 36..37),
+            (Token::Path("👍 ok.mov".to_string()), 38..51),
+        ]);
+    }
+
+    #[test]
+    fn test_tokenize_glob_patterns() {
+        let mut tokenizer = Tokenizer::new("convert all *.mov in ./raw");
+        let tokens = tokenizer.tokenize();
+
+        assert_eq!(tokens, vec![
+            Token::Word("convert".to_string()),
+            Token::Word("all".to_string()),
+            Token::Path("*.mov".to_string()),
+            Token::Word("in".to_string()),
+            Token::Path("./raw".to_string()),
+        ]);
+    }
+}
@@ -1,5 +1,7 @@
 //! Intent module for the FF CLI tool.
 //!
-//! This module defines the structured representation of user commands.
+//! This module defines the structured representation of user commands and
+//! the typed settings each operation takes.
 
+pub mod params;
 pub mod types;
\ No newline at end of file
@@ -0,0 +1,641 @@
+use crate::utils::codecs::Codec;
+use crate::utils::time;
+use std::fmt;
+
+/// Error types for parameter values that are out of range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    /// A size of zero pixels
+    ZeroSize,
+    /// A frame rate outside the supported range, in frames per second
+    FrameRate(f64),
+    /// A time between frames that is not positive, in seconds
+    Interval(f64),
+    /// An audio bitrate outside the supported range, in kilobits per second
+    Bitrate(u32),
+    /// A length that is not positive, in seconds
+    Duration(f64),
+    /// A time range whose end, in seconds, is not after its start
+    EndBeforeStart { start: f64, end: f64 },
+    /// A time range with neither a start, an end nor a length
+    Unbounded,
+    /// A time in seconds that is negative or not a number
+    Time(f64),
+    /// A number of frames to take that is not positive
+    FrameCount(f64),
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::ZeroSize => write!(f, "0 pixels is not a valid size"),
+            ParamError::FrameRate(fps) => {
+                write!(f, "{} fps is not a valid frame rate; use more than 0 and at most {} fps", fps, FrameRate::MAX)
+            },
+            ParamError::Interval(seconds) => {
+                write!(f, "one frame every {} seconds is not a valid rate; the interval must be more than 0 seconds", seconds)
+            },
+            ParamError::Bitrate(kbps) => write!(
+                f,
+                "{} kbps is not a valid audio bitrate; use {} to {} kbps",
+                kbps,
+                Bitrate::MIN_KBPS,
+                Bitrate::MAX_KBPS
+            ),
+            ParamError::Duration(seconds) => {
+                write!(f, "a length of {} seconds is not valid; it must be more than 0 seconds", seconds)
+            },
+            ParamError::EndBeforeStart { start, end } => write!(
+                f,
+                "end time {} is not after start time {}",
+                time::format_timestamp(*end),
+                time::format_timestamp(*start)
+            ),
+            ParamError::Unbounded => write!(f, "a time range needs a start, an end or a length"),
+            ParamError::Time(seconds) => write!(f, "{} seconds is not a valid time; it must be 0 or more", seconds),
+            ParamError::FrameCount(count) => write!(f, "{} frames is not a valid number of frames; ask for at least one", count),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// A length in pixels, which is never zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u32", into = "u32"))]
+pub struct Pixels(u32);
+
+impl Pixels {
+    /// Creates a length in pixels, rejecting zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ffrs::intent::params::Pixels;
+    /// assert_eq!(Pixels::new(480).unwrap().get(), 480);
+    /// assert!(Pixels::new(0).is_err());
+    /// ```
+    pub fn new(value: u32) -> Result<Self, ParamError> {
+        if value == 0 {
+            return Err(ParamError::ZeroSize);
+        }
+        Ok(Pixels(value))
+    }
+
+    /// Returns the length in pixels.
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl TryFrom<u32> for Pixels {
+    type Error = ParamError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Pixels::new(value)
+    }
+}
+
+impl From<Pixels> for u32 {
+    fn from(pixels: Pixels) -> Self {
+        pixels.0
+    }
+}
+
+impl fmt::Display for Pixels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A target frame size for a resize.
+///
+/// When only one side is given, the other follows the aspect ratio and is
+/// rounded to an even number, which most encoders require.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Resolution {
+    /// Exactly this width and height
+    Exact { width: Pixels, height: Pixels },
+    /// This width
+    Width(Pixels),
+    /// This height
+    Height(Pixels),
+    /// Half the input width
+    Half,
+    /// A quarter of the input width
+    Quarter,
+    /// Twice the input width
+    Double,
+}
+
+impl Default for Resolution {
+    /// Full HD, 1920x1080, used when a resize names no size.
+    fn default() -> Self {
+        Resolution::Exact { width: Pixels(1920), height: Pixels(1080) }
+    }
+}
+
+impl Resolution {
+    /// Creates an exact size, rejecting zero pixels.
+    pub fn exact(width: u32, height: u32) -> Result<Self, ParamError> {
+        Ok(Resolution::Exact { width: Pixels::new(width)?, height: Pixels::new(height)? })
+    }
+
+    /// Creates a size from a width, rejecting zero pixels.
+    pub fn width(width: u32) -> Result<Self, ParamError> {
+        Ok(Resolution::Width(Pixels::new(width)?))
+    }
+
+    /// Creates a size from a height, rejecting zero pixels.
+    pub fn height(height: u32) -> Result<Self, ParamError> {
+        Ok(Resolution::Height(Pixels::new(height)?))
+    }
+
+    /// Returns the width and height arguments of ffmpeg's `scale` filter, e.g. `("1280", "-2")`.
+    pub fn scale_arguments(&self) -> (String, String) {
+        let auto = "-2".to_string();
+        match self {
+            Resolution::Exact { width, height } => (width.to_string(), height.to_string()),
+            Resolution::Width(width) => (width.to_string(), auto),
+            Resolution::Height(height) => (auto, height.to_string()),
+            Resolution::Half => ("iw/2".to_string(), auto),
+            Resolution::Quarter => ("iw/4".to_string(), auto),
+            Resolution::Double => ("iw*2".to_string(), auto),
+        }
+    }
+}
+
+impl fmt::Display for Resolution {
+    /// Writes the size the way a command would say it, e.g. `1280x720` or `1280 wide`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resolution::Exact { width, height } => write!(f, "{}x{}", width, height),
+            Resolution::Width(width) => write!(f, "{} wide", width),
+            Resolution::Height(height) => write!(f, "{} tall", height),
+            Resolution::Half => write!(f, "half size"),
+            Resolution::Quarter => write!(f, "quarter size"),
+            Resolution::Double => write!(f, "double size"),
+        }
+    }
+}
+
+/// How often frames are taken from a video.
+///
+/// A rate can only be made through its checked constructors, so it is
+/// always positive; `rate` gives its value to match on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Rate", into = "Rate"))]
+pub struct FrameRate(Rate);
+
+/// The value of a `FrameRate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Rate {
+    /// This many frames per second
+    PerSecond(f64),
+    /// One frame every this many seconds
+    Every(f64),
+}
+
+impl FrameRate {
+    /// The highest frame rate accepted, in frames per second.
+    pub const MAX: f64 = 120.0;
+
+    /// Creates a rate in frames per second, which must be above 0 and at most `MAX`.
+    pub fn per_second(fps: f64) -> Result<Self, ParamError> {
+        if fps > 0.0 && fps <= Self::MAX {
+            Ok(FrameRate(Rate::PerSecond(fps)))
+        } else {
+            Err(ParamError::FrameRate(fps))
+        }
+    }
+
+    /// Creates a rate of one frame every `seconds`, which must be positive.
+    pub fn every(seconds: f64) -> Result<Self, ParamError> {
+        if seconds > 0.0 && seconds.is_finite() {
+            Ok(FrameRate(Rate::Every(seconds)))
+        } else {
+            Err(ParamError::Interval(seconds))
+        }
+    }
+
+    /// Returns the value of the rate.
+    pub fn rate(&self) -> Rate {
+        self.0
+    }
+
+    /// Returns the rate as ffmpeg's `fps` filter takes it, e.g. `2` or `1/10`.
+    pub fn filter_value(&self) -> String {
+        match self.0 {
+            Rate::PerSecond(fps) => fps.to_string(),
+            Rate::Every(seconds) => format!("1/{}", seconds),
+        }
+    }
+}
+
+impl TryFrom<Rate> for FrameRate {
+    type Error = ParamError;
+
+    fn try_from(rate: Rate) -> Result<Self, Self::Error> {
+        match rate {
+            Rate::PerSecond(fps) => FrameRate::per_second(fps),
+            Rate::Every(seconds) => FrameRate::every(seconds),
+        }
+    }
+}
+
+impl From<FrameRate> for Rate {
+    fn from(frame_rate: FrameRate) -> Self {
+        frame_rate.0
+    }
+}
+
+impl fmt::Display for FrameRate {
+    /// Writes the rate in words, e.g. `2 frames per second` or `one frame every 10 seconds`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Rate::PerSecond(1.0) => write!(f, "one frame per second"),
+            Rate::PerSecond(fps) => write!(f, "{} frames per second", fps),
+            Rate::Every(seconds) => write!(f, "one frame every {} seconds", seconds),
+        }
+    }
+}
+
+/// An audio bitrate in kilobits per second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u32", into = "u32"))]
+pub struct Bitrate(u32);
+
+impl Bitrate {
+    /// The lowest bitrate accepted, in kilobits per second.
+    pub const MIN_KBPS: u32 = 8;
+    /// The highest bitrate accepted, in kilobits per second.
+    pub const MAX_KBPS: u32 = 1024;
+
+    /// Creates a bitrate, which must lie between `MIN_KBPS` and `MAX_KBPS`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ffrs::intent::params::Bitrate;
+    /// assert_eq!(Bitrate::from_kbps(192).unwrap().to_string(), "192 kbps");
+    /// assert!(Bitrate::from_kbps(5000).is_err());
+    /// ```
+    pub fn from_kbps(kbps: u32) -> Result<Self, ParamError> {
+        if (Self::MIN_KBPS..=Self::MAX_KBPS).contains(&kbps) {
+            Ok(Bitrate(kbps))
+        } else {
+            Err(ParamError::Bitrate(kbps))
+        }
+    }
+
+    /// Returns the bitrate in kilobits per second.
+    pub fn kbps(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns the bitrate as ffmpeg takes it, e.g. `192k`.
+    pub fn ffmpeg_value(&self) -> String {
+        format!("{}k", self.0)
+    }
+}
+
+impl TryFrom<u32> for Bitrate {
+    type Error = ParamError;
+
+    fn try_from(kbps: u32) -> Result<Self, Self::Error> {
+        Bitrate::from_kbps(kbps)
+    }
+}
+
+impl From<Bitrate> for u32 {
+    fn from(bitrate: Bitrate) -> Self {
+        bitrate.0
+    }
+}
+
+impl fmt::Display for Bitrate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} kbps", self.0)
+    }
+}
+
+/// How an extracted audio track is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Quality {
+    /// The usual quality setting for the output format
+    #[default]
+    Default,
+    /// A constant bitrate
+    Bitrate(Bitrate),
+    /// The original stream, copied without re-encoding. `source` is its codec
+    /// once the input has been inspected, and `None` until then or when the
+    /// codec is not a known one.
+    Copy { source: Option<Codec> },
+}
+
+/// A part of a clip, with times in seconds from its beginning.
+///
+/// A range can only be made through its checked constructors, so its times
+/// are never negative and its end always comes after its start; `span`
+/// gives its value to match on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TimeSpan", into = "TimeSpan"))]
+pub struct TimeRange(TimeSpan);
+
+/// The value of a `TimeRange`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TimeSpan {
+    /// From `start` to `end`, where a missing side means the clip's own beginning or end
+    Between { start: Option<f64>, end: Option<f64> },
+    /// `duration` seconds from `start`, or from the beginning
+    For { start: Option<f64>, duration: f64 },
+    /// The last `duration` seconds
+    Last(f64),
+}
+
+impl TimeRange {
+    /// Creates a range between two times, at least one of which must be given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ffrs::intent::params::TimeRange;
+    /// assert!(TimeRange::between(Some(90.0), Some(165.0)).is_ok());
+    /// assert_eq!(
+    ///     TimeRange::between(Some(20.0), Some(10.0)).unwrap_err().to_string(),
+    ///     "end time 00:00:10 is not after start time 00:00:20"
+    /// );
+    /// ```
+    pub fn between(start: Option<f64>, end: Option<f64>) -> Result<Self, ParamError> {
+        let (start, end) = (start.map(time_point).transpose()?, end.map(time_point).transpose()?);
+        match (start, end) {
+            (None, None) => Err(ParamError::Unbounded),
+            (Some(start), Some(end)) if end <= start => Err(ParamError::EndBeforeStart { start, end }),
+            _ => Ok(TimeRange(TimeSpan::Between { start, end })),
+        }
+    }
+
+    /// Creates a range of `duration` seconds from `start`, or from the beginning.
+    pub fn starting_for(start: Option<f64>, duration: f64) -> Result<Self, ParamError> {
+        let start = start.map(time_point).transpose()?;
+        Ok(TimeRange(TimeSpan::For { start, duration: positive(duration)? }))
+    }
+
+    /// Creates a range covering the last `duration` seconds.
+    pub fn last(duration: f64) -> Result<Self, ParamError> {
+        Ok(TimeRange(TimeSpan::Last(positive(duration)?)))
+    }
+
+    /// Returns the value of the range.
+    pub fn span(&self) -> TimeSpan {
+        self.0
+    }
+
+    /// Returns where the range starts, if it names a start time.
+    pub fn start(&self) -> Option<f64> {
+        match self.0 {
+            TimeSpan::Between { start, .. } | TimeSpan::For { start, .. } => start,
+            TimeSpan::Last(_) => None,
+        }
+    }
+
+    /// Returns how long the range is, in seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_duration` - Called for the length of the input when the range runs to its end
+    pub fn length(&self, input_duration: impl FnOnce() -> Option<f64>) -> Option<f64> {
+        match self.0 {
+            TimeSpan::Between { start, end } => {
+                let end = end.or_else(input_duration)?;
+                Some((end - start.unwrap_or_default()).max(0.0))
+            },
+            TimeSpan::For { duration, .. } | TimeSpan::Last(duration) => Some(duration),
+        }
+    }
+}
+
+impl TryFrom<TimeSpan> for TimeRange {
+    type Error = ParamError;
+
+    fn try_from(span: TimeSpan) -> Result<Self, Self::Error> {
+        match span {
+            TimeSpan::Between { start, end } => TimeRange::between(start, end),
+            TimeSpan::For { start, duration } => TimeRange::starting_for(start, duration),
+            TimeSpan::Last(duration) => TimeRange::last(duration),
+        }
+    }
+}
+
+impl From<TimeRange> for TimeSpan {
+    fn from(range: TimeRange) -> Self {
+        range.0
+    }
+}
+
+/// A time in seconds from the beginning of a clip, which is never negative.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "f64", into = "f64"))]
+pub struct TimePoint(f64);
+
+impl TimePoint {
+    /// Creates a time, rejecting negative and non-finite values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ffrs::intent::params::TimePoint;
+    /// assert_eq!(TimePoint::new(83.0).unwrap().seconds(), 83.0);
+    /// assert!(TimePoint::new(-1.0).is_err());
+    /// ```
+    pub fn new(seconds: f64) -> Result<Self, ParamError> {
+        time_point(seconds).map(TimePoint)
+    }
+
+    /// Returns the time in seconds.
+    pub fn seconds(&self) -> f64 {
+        self.0
+    }
+}
+
+impl TryFrom<f64> for TimePoint {
+    type Error = ParamError;
+
+    fn try_from(seconds: f64) -> Result<Self, Self::Error> {
+        TimePoint::new(seconds)
+    }
+}
+
+impl From<TimePoint> for f64 {
+    fn from(time: TimePoint) -> Self {
+        time.0
+    }
+}
+
+/// Checks that a length in seconds is positive.
+fn positive(seconds: f64) -> Result<f64, ParamError> {
+    if seconds > 0.0 && seconds.is_finite() {
+        Ok(seconds)
+    } else {
+        Err(ParamError::Duration(seconds))
+    }
+}
+
+/// Checks that a time in seconds is not negative.
+fn time_point(seconds: f64) -> Result<f64, ParamError> {
+    if seconds >= 0.0 && seconds.is_finite() {
+        Ok(seconds)
+    } else {
+        Err(ParamError::Time(seconds))
+    }
+}
+
+/// How a trim cuts its clip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TrimMode {
+    /// Copy the streams, which is fast but cuts at the nearest keyframes
+    #[default]
+    Copy,
+    /// Re-encode, which cuts at the exact frame
+    Accurate,
+}
+
+/// How a join combines its inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ConcatMethod {
+    /// Decode and re-encode everything with the concat filter, joining the
+    /// video, the audio, or both
+    Filter { video: bool, audio: bool },
+    /// Copy the streams with the concat demuxer, which needs inputs with matching codecs
+    Demuxer,
+}
+
+impl Default for ConcatMethod {
+    fn default() -> Self {
+        ConcatMethod::Filter { video: true, audio: true }
+    }
+}
+
+/// What an animated GIF is made from.
+///
+/// Each field is checked by its own type, so every combination is valid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GifOptions {
+    /// The part of the video to use, or `None` for all of it
+    pub range: Option<TimeRange>,
+    /// The frame rate of the GIF
+    pub fps: FrameRate,
+    /// The width of the GIF; the height follows the aspect ratio
+    pub width: Pixels,
+}
+
+impl Default for GifOptions {
+    /// The whole video at 10 frames per second, 480 pixels wide.
+    fn default() -> Self {
+        Self { range: None, fps: FrameRate(Rate::PerSecond(10.0)), width: Pixels(480) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constructors_reject_values_with_units() {
+        assert_eq!(Resolution::width(0), Err(ParamError::ZeroSize));
+        assert_eq!(
+            FrameRate::per_second(240.0).unwrap_err().to_string(),
+            "240 fps is not a valid frame rate; use more than 0 and at most 120 fps"
+        );
+        assert!(FrameRate::every(0.0).is_err());
+        assert_eq!(
+            Bitrate::from_kbps(4).unwrap_err().to_string(),
+            "4 kbps is not a valid audio bitrate; use 8 to 1024 kbps"
+        );
+        assert_eq!(TimeRange::between(None, None), Err(ParamError::Unbounded));
+        assert_eq!(TimeRange::last(0.0), Err(ParamError::Duration(0.0)));
+    }
+
+    #[test]
+    fn test_invalid_values_are_unrepresentable() {
+        assert_eq!(Pixels::new(0), Err(ParamError::ZeroSize));
+        assert_eq!(Resolution::exact(1280, 0), Err(ParamError::ZeroSize));
+        assert_eq!(FrameRate::per_second(-1.0), Err(ParamError::FrameRate(-1.0)));
+        assert!(FrameRate::every(f64::INFINITY).is_err());
+        assert_eq!(
+            TimeRange::between(Some(10.0), Some(5.0)),
+            Err(ParamError::EndBeforeStart { start: 10.0, end: 5.0 })
+        );
+        assert_eq!(TimeRange::between(Some(-1.0), None), Err(ParamError::Time(-1.0)));
+        assert_eq!(TimeRange::starting_for(Some(-1.0), 3.0), Err(ParamError::Time(-1.0)));
+        assert!(TimeRange::starting_for(None, f64::NAN).is_err());
+        assert_eq!(TimePoint::new(-1.0), Err(ParamError::Time(-1.0)));
+        assert!(TimePoint::new(f64::INFINITY).is_err());
+
+        // Values converted back from their parts pass through the same checks.
+        assert_eq!(FrameRate::try_from(Rate::PerSecond(-1.0)), Err(ParamError::FrameRate(-1.0)));
+        assert_eq!(
+            TimeRange::try_from(TimeSpan::Between { start: Some(10.0), end: Some(5.0) }),
+            Err(ParamError::EndBeforeStart { start: 10.0, end: 5.0 })
+        );
+        assert_eq!(TimeRange::try_from(TimeSpan::Last(-2.0)), Err(ParamError::Duration(-2.0)));
+        assert_eq!(Pixels::try_from(0), Err(ParamError::ZeroSize));
+        assert_eq!(TimePoint::try_from(-5.0), Err(ParamError::Time(-5.0)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserializing_checks_values() {
+        let gif: GifOptions = serde_json::from_str(
+            r#"{"range":{"for":{"start":5.0,"duration":3.0}},"fps":{"per_second":15.0},"width":320}"#,
+        ).unwrap();
+        assert_eq!(gif.width.get(), 320);
+        assert_eq!(serde_json::to_string(&gif.fps).unwrap(), r#"{"per_second":15.0}"#);
+
+        assert!(serde_json::from_str::<GifOptions>(r#"{"range":null,"fps":{"per_second":10.0},"width":0}"#).is_err());
+        assert!(serde_json::from_str::<Resolution>(r#"{"width":0}"#).is_err());
+        assert!(serde_json::from_str::<Resolution>(r#"{"exact":{"width":1280,"height":0}}"#).is_err());
+        assert!(serde_json::from_str::<FrameRate>(r#"{"per_second":-1.0}"#).is_err());
+        assert!(serde_json::from_str::<FrameRate>(r#"{"every":0.0}"#).is_err());
+        assert!(serde_json::from_str::<TimeRange>(r#"{"between":{"start":10.0,"end":5.0}}"#).is_err());
+        assert!(serde_json::from_str::<TimeRange>(r#"{"between":{"start":null,"end":null}}"#).is_err());
+        assert!(serde_json::from_str::<TimeRange>(r#"{"last":0.0}"#).is_err());
+        assert!(serde_json::from_str::<TimePoint>("-1.0").is_err());
+        assert_eq!(serde_json::from_str::<TimePoint>("83.0").unwrap().seconds(), 83.0);
+    }
+
+    #[test]
+    fn test_ffmpeg_values() {
+        assert_eq!(Resolution::width(1280).unwrap().scale_arguments(), ("1280".to_string(), "-2".to_string()));
+        assert_eq!(Resolution::Half.scale_arguments(), ("iw/2".to_string(), "-2".to_string()));
+        assert_eq!(FrameRate::every(10.0).unwrap().filter_value(), "1/10");
+        assert_eq!(FrameRate::per_second(2.0).unwrap().to_string(), "2 frames per second");
+        assert_eq!(Bitrate::from_kbps(192).unwrap().ffmpeg_value(), "192k");
+    }
+
+    #[test]
+    fn test_time_range_length() {
+        let range = TimeRange::between(Some(10.0), None).unwrap();
+        assert_eq!(range.length(|| Some(60.0)), Some(50.0));
+        assert_eq!(range.length(|| None), None);
+        assert_eq!(TimeRange::starting_for(Some(5.0), 3.0).unwrap().length(|| None), Some(3.0));
+    }
+}
@@ -1,7 +1,12 @@
-use std::path::PathBuf;
+use crate::intent::params::{ConcatMethod, FrameRate, GifOptions, Quality, Resolution, TimePoint, TimeRange, TrimMode};
+use crate::utils::codecs::Codec;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Types of operations that can be performed by the FF CLI tool.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum OperationType {
     /// Convert a media file from one format to another
     Convert,
@@ -11,17 +16,116 @@ pub enum OperationType {
     Transcode,
     /// Extract audio from a media file
     ExtractAudio,
+    /// Cut a time range out of a media file
+    Trim,
+    /// Join several media files one after another
+    Concat,
+    /// Turn a video into an animated GIF
+    Gif,
+    /// Save a single frame of a video as an image
+    Thumbnail,
+    /// Save frames of a video at a regular interval as numbered images
+    ExtractFrames,
+    /// Report a media file's container and streams without changing it
+    Inspect,
+    /// Run several operations one after another in a single pass
+    Pipeline,
+}
+
+/// An operation together with the settings that apply to it.
+///
+/// Each operation carries only the settings it understands, so a trim
+/// cannot be given a codec and a resize cannot be given a bitrate.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Operation {
+    /// Convert a media file from one format to another
+    Convert,
+    /// Resize a video to a new frame size
+    Resize(Resolution),
+    /// Re-encode with specific codecs; a codec that is not given uses the default
+    Transcode { video: Option<Codec>, audio: Option<Codec> },
+    /// Extract the audio track with the given quality
+    ExtractAudio(Quality),
+    /// Cut a time range out of a media file
+    Trim { range: TimeRange, mode: TrimMode },
+    /// Join several media files one after another
+    Concat(ConcatMethod),
+    /// Turn a video into an animated GIF
+    Gif(GifOptions),
+    /// Save a single frame, at a given time or picked by ffmpeg
+    Thumbnail { time: Option<TimePoint> },
+    /// Save frames at a regular rate as numbered images
+    ExtractFrames(FrameRate),
+    /// Report a media file's container and streams without changing it
+    Inspect,
+    /// Apply several operations in order, each to the result of the one before
+    ///
+    /// The steps are fused into one ffmpeg run, so no intermediate files are written.
+    Pipeline(Vec<Operation>),
+}
+
+impl Operation {
+    /// Returns which kind of operation this is, without its settings.
+    pub fn kind(&self) -> OperationType {
+        match self {
+            Operation::Convert => OperationType::Convert,
+            Operation::Resize(_) => OperationType::Resize,
+            Operation::Transcode { .. } => OperationType::Transcode,
+            Operation::ExtractAudio(_) => OperationType::ExtractAudio,
+            Operation::Trim { .. } => OperationType::Trim,
+            Operation::Concat(_) => OperationType::Concat,
+            Operation::Gif(_) => OperationType::Gif,
+            Operation::Thumbnail { .. } => OperationType::Thumbnail,
+            Operation::ExtractFrames(_) => OperationType::ExtractFrames,
+            Operation::Inspect => OperationType::Inspect,
+            Operation::Pipeline(_) => OperationType::Pipeline,
+        }
+    }
 }
 
 /// Represents a user's intent to perform a media conversion operation.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Intent {
-    /// The type of operation to perform
-    pub operation: OperationType,
-    /// The path to the input media file
-    pub input_path: PathBuf,
+    /// The operation to perform, with its settings
+    pub operation: Operation,
+    /// The paths to the input media files, in order
+    ///
+    /// Most operations take exactly one input; `Concat` takes two or more.
+    pub input_paths: Vec<PathBuf>,
     /// The path where the output media file should be saved
+    ///
+    /// Empty for `Inspect`, which writes no file.
     pub output_path: PathBuf,
-    /// Additional parameters for the operation
-    pub parameters: std::collections::HashMap<String, String>,
-}
\ No newline at end of file
+    /// The words of the request behind each part of the intent
+    ///
+    /// Keyed by `operation`, `input`, `output` and the setting they chose:
+    /// `size`, `vcodec`, `acodec`, `bitrate`, `copy`, `start`, `end`,
+    /// `duration`, `from_end`, `mode`, `fps`, `width` or `time`. Parts that
+    /// were filled in by default have no entry.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "serialize_sorted")
+    )]
+    pub sources: HashMap<String, String>,
+}
+
+impl Intent {
+    /// Returns the first input, which single-input operations work on.
+    pub fn input_path(&self) -> Option<&Path> {
+        self.input_paths.first().map(PathBuf::as_path)
+    }
+
+    /// Returns the words of the request that set the given part of the intent.
+    pub fn source(&self, key: &str) -> Option<&str> {
+        self.sources.get(key).map(String::as_str)
+    }
+}
+
+/// Writes a map with its keys in order, so the same intent always serializes the same way.
+#[cfg(feature = "serde")]
+fn serialize_sorted<S: serde::Serializer>(map: &HashMap<String, String>, serializer: S) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&map.iter().collect::<std::collections::BTreeMap<_, _>>(), serializer)
+}
@@ -18,7 +18,8 @@ pub mod executor;
 pub mod utils;
 
 pub use grammar::{Tokenizer, Parser};
-pub use intent::types::{Intent, OperationType};
-pub use command_builder::CommandBuilder;
+pub use intent::types::{Intent, Operation, OperationType};
+pub use command_builder::{CommandBuilder, FfmpegCommand};
 pub use executor::runner::Runner;
+pub use executor::media_info::MediaInfo;
 pub use utils::file_utils;
\ No newline at end of file
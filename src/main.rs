@@ -1,20 +1,23 @@
-use clap::Parser as ClapParser;
-use std::io::{self, Write};
+use clap::{Parser as ClapParser, Subcommand};
+use std::io::{self, IsTerminal, Write};
 
-mod grammar;
-mod intent;
-mod command_builder;
-mod executor;
-mod utils;
-
-use grammar::{Tokenizer, Parser as GrammarParser};
-use command_builder::CommandBuilder;
-use executor::runner::Runner;
+use ffrs::{Parser as GrammarParser, CommandBuilder, FfmpegCommand, Runner, MediaInfo, Intent, Operation};
+#[cfg(feature = "serde")]
+use ffrs::command_builder::plan::{self, Plan};
+use ffrs::grammar::describe;
+use ffrs::executor::batch::JobStatus;
+use ffrs::executor::probe;
+use ffrs::executor::progress::{ConsoleProgress, Progress};
+use ffrs::executor::queue::{FailureMode, Job, JobEvent, JobQueue};
+use ffrs::executor::runner::{ExecutionError, OverwritePolicy};
 
 #[derive(ClapParser)]
 #[command(name = "ff")]
 #[command(about = "A CLI tool that translates plain English commands into ffmpeg commands")]
 struct Cli {
+    #[command(subcommand)]
+    action: Option<Action>,
+
     #[arg(value_parser)]
     command: Option<String>,
 
@@ -24,24 +27,102 @@ struct Cli {
     #[arg(long, default_value_t = false)]
     dry_run: bool,
 
+    #[arg(long, default_value_t = false)]
+    explain: bool,
+
+    #[cfg(feature = "serde")]
+    #[arg(long, default_value_t = false, conflicts_with_all = ["explain", "interactive"])]
+    json: bool,
+
     #[arg(long)]
     output: Option<String>,
+
+    #[arg(short, long)]
+    jobs: Option<usize>,
+
+    #[arg(long, default_value_t = false)]
+    fail_fast: bool,
+
+    #[arg(short = 'y', long, default_value_t = false, group = "existing_output")]
+    overwrite: bool,
+
+    #[arg(long, default_value_t = false, group = "existing_output")]
+    no_clobber: bool,
+
+    #[arg(long, default_value_t = false, group = "existing_output")]
+    rename: bool,
+}
+
+#[derive(Subcommand)]
+enum Action {
+    /// Describe an existing ffmpeg command in plain English
+    Describe {
+        /// The ffmpeg command, quoted as one argument or given as separate words
+        #[arg(required = true, num_args = 1.., allow_hyphen_values = true, trailing_var_arg = true)]
+        command: Vec<String>,
+    },
+    /// Run a plan written by --json, after checking that it was not changed
+    #[cfg(feature = "serde")]
+    Run {
+        /// The plan file, or - to read it from standard input
+        plan: String,
+    },
+}
+
+/// Options that apply to every command run in this session.
+struct Settings {
+    dry_run: bool,
+    explain: bool,
+    #[cfg(feature = "serde")]
+    json: bool,
+    output: Option<String>,
+    runner: Runner,
+    queue: JobQueue,
 }
 
 fn main() {
     let args = Cli::parse();
 
+    if let Some(Action::Describe { command }) = &args.action {
+        run_describe_mode(command);
+        return;
+    }
+
+    let failure_mode = if args.fail_fast { FailureMode::FailFast } else { FailureMode::KeepGoing };
+    let overwrite_policy = if args.overwrite {
+        OverwritePolicy::Overwrite
+    } else if args.rename {
+        OverwritePolicy::Rename
+    } else {
+        OverwritePolicy::NoClobber
+    };
+    let settings = Settings {
+        dry_run: args.dry_run,
+        explain: args.explain,
+        #[cfg(feature = "serde")]
+        json: args.json,
+        output: args.output,
+        runner: Runner::new().overwrite_policy(overwrite_policy),
+        queue: JobQueue::new(args.jobs.unwrap_or_else(JobQueue::default_workers)).failure_mode(failure_mode),
+    };
+
+    #[cfg(feature = "serde")]
+    if let Some(Action::Run { plan }) = &args.action {
+        run_plan_mode(plan, &settings);
+        return;
+    }
+
     if args.interactive {
-        run_interactive_mode(args.dry_run);
+        run_interactive_mode(&settings);
     } else if let Some(command) = args.command {
-        run_direct_mode(&command, args.dry_run, args.output);
+        run_direct_mode(&command, &settings);
     } else {
         eprintln!("Error: No command provided. Use --help for usage information.");
         std::process::exit(1);
     }
 }
 
-fn run_interactive_mode(dry_run: bool) {
+fn run_interactive_mode(settings: &Settings) {
     println!("FF - Media Conversion Tool (Interactive Mode)");
     println!("Enter 'quit' or 'exit' to exit the program");
 
@@ -59,7 +140,7 @@ fn run_interactive_mode(dry_run: bool) {
                 }
 
                 if !input.is_empty() {
-                    match process_command(input, dry_run, None) {
+                    match process_command(input, settings) {
                         Ok(_) => {},
                         Err(e) => eprintln!("Error: {}", e),
                     }
@@ -73,9 +154,9 @@ fn run_interactive_mode(dry_run: bool) {
     }
 }
 
-fn run_direct_mode(command: &str, dry_run: bool, output: Option<String>) {
-    match process_command(command, dry_run, output) {
-        Ok(_) => std::process::exit(0), // Success
+fn run_direct_mode(command: &str, settings: &Settings) {
+    match process_command(command, settings) {
+        Ok(code) => std::process::exit(code), // Success, or a batch with failed jobs
         Err(e) => {
             eprintln!("Error: {}", e);
             std::process::exit(1); // User input error
@@ -83,23 +164,163 @@ fn run_direct_mode(command: &str, dry_run: bool, output: Option<String>) {
     }
 }
 
-fn process_command(command: &str, dry_run: bool, output: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
-    let mut tokenizer = Tokenizer::new(command);
-    let tokens = tokenizer.tokenize();
+/// Prints the plain English equivalent of an ffmpeg command, noting the options it leaves out.
+fn run_describe_mode(command: &[String]) {
+    let description = match command {
+        [command_line] => describe::describe_command(command_line),
+        args => describe::describe_args(args),
+    };
+
+    match description {
+        Ok(description) => {
+            println!("{}", description.sentence());
+            for option in &description.unsupported {
+                eprintln!("Note: '{}' has no plain English equivalent yet and was left out.", option);
+            }
+        },
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            eprintln!("Guidance: Pass the whole command in quotes, e.g. ffrs describe \"ffmpeg -i in.mkv out.mp4\"");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Reads a plan file, checks each plan against its intent and runs the intents.
+#[cfg(feature = "serde")]
+fn run_plan_mode(path: &str, settings: &Settings) {
+    let json = match path {
+        "-" => io::read_to_string(io::stdin()),
+        path => std::fs::read_to_string(path),
+    };
+    let plans = match json {
+        Ok(json) => plan::plans_from_json(&json),
+        Err(e) => {
+            eprintln!("Error: Cannot read {}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
 
-    let mut parser = GrammarParser::new(tokens);
-    let intent = match parser.parse() {
-        Ok(intent) => intent,
+    let result = plans.and_then(|plans| {
+        plans.into_iter().map(|plan| plan.verify().map(|_| plan.intent)).collect::<Result<Vec<_>, _>>()
+    });
+    let intents = match result {
+        Ok(intents) => intents,
         Err(e) => {
-            eprintln!("Parse Error: {}", e);
-            eprintln!("Guidance: Make sure your command follows the format 'convert <input> to <output>' or similar.");
-            eprintln!("Example: 'convert video.mp4 to video.avi'");
+            eprintln!("Error: {}", e);
+            eprintln!("Guidance: Change a plan through its intent, or write it again with ffrs --json \"<command>\".");
+            std::process::exit(1);
+        }
+    };
+
+    match run_intents(intents, settings) {
+        Ok(code) => std::process::exit(code),
+        Err(_) => std::process::exit(1),
+    }
+}
+
+/// Parses a command and runs it, or prints its plan with `--json`.
+///
+/// # Returns
+///
+/// The exit status to report: `0` on success, or the batch's aggregated
+/// status when the command named several files.
+fn process_command(command: &str, settings: &Settings) -> Result<i32, Box<dyn std::error::Error>> {
+    let mut parser = GrammarParser::for_command(command);
+    let mut intents = match parser.parse_all() {
+        Ok(intents) => intents,
+        Err(e) => {
+            let diagnostic = parser.diagnose(e);
+            eprintln!("Parse Error: {}", diagnostic.error);
+            eprint!("{}", diagnostic.render(command));
+            return Err(Box::new(diagnostic));
+        }
+    };
+
+    for intent in &mut intents {
+        if let Err(e) = probe::choose_concat_method(intent) {
+            eprintln!("Note: could not inspect the input files ({}); joining with the concat filter.", e);
+        }
+        if let Err(e) = probe::identify_copied_audio(intent) {
+            eprintln!("Note: could not inspect the input file ({}); its audio codec is unknown.", e);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    if settings.json {
+        return print_plans(intents, settings).map(|_| 0);
+    }
+    run_intents(intents, settings)
+}
+
+/// Prints the plan of each intent as JSON without running anything.
+///
+/// Each intent's output path is set to where its command writes, so the
+/// plan runs the same way without `--output`.
+#[cfg(feature = "serde")]
+fn print_plans(intents: Vec<Intent>, settings: &Settings) -> Result<(), Box<dyn std::error::Error>> {
+    let mut plans = Vec::new();
+    for mut intent in intents {
+        let output_dir = settings.output.as_deref().filter(|_| intent.operation != Operation::Inspect);
+        let command = prepare_command(&intent, output_dir)?;
+        if let Some(output) = command.get_output() {
+            intent.output_path = output.to_path_buf();
+        }
+        plans.push(Plan::new(intent, &command));
+    }
+
+    println!("{}", plan::plans_to_json(&plans));
+    Ok(())
+}
+
+/// Builds and runs intents: one on its own, several as a batch.
+fn run_intents(mut intents: Vec<Intent>, settings: &Settings) -> Result<i32, Box<dyn std::error::Error>> {
+    if intents.len() > 1 {
+        return run_batch(intents, settings);
+    }
+
+    let intent = intents.remove(0);
+
+    if intent.operation == Operation::Inspect {
+        return inspect_media(&intent, settings.dry_run).map(|_| 0);
+    }
+
+    let ffmpeg_cmd = prepare_command(&intent, settings.output.as_deref())?;
+    let final_cmd = match settings.runner.resolve_output(&ffmpeg_cmd) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            eprintln!("Execution Error: {}", e);
+            eprintln!("Guidance: Use --overwrite to replace it, --rename to keep both, or choose another output name.");
             return Err(Box::new(e));
         }
     };
 
+    if settings.explain {
+        println!("{}", final_cmd.explained());
+    } else {
+        println!("{}", final_cmd);
+    }
+
+    if !settings.dry_run {
+        let duration = probe::expected_duration(&intent);
+        let mut reporter = ConsoleProgress::new(io::stdout(), io::stdout().is_terminal());
+        match settings.runner.execute_with_progress(&final_cmd, duration, &mut reporter) {
+            Ok(_) => {},
+            Err(e) => {
+                eprintln!("Execution Error: {}", e);
+                eprintln!("Guidance: Make sure ffmpeg is installed and accessible in your PATH.");
+                return Err(Box::new(e));
+            }
+        }
+    }
+
+    Ok(0)
+}
+
+/// Builds the ffmpeg command for an intent, moving its output into `output_dir` when given.
+fn prepare_command(intent: &Intent, output_dir: Option<&str>) -> Result<FfmpegCommand, Box<dyn std::error::Error>> {
     let cmd_builder = CommandBuilder::new();
-    let ffmpeg_cmd = match cmd_builder.build_command(&intent) {
+    let ffmpeg_cmd = match cmd_builder.build_command(intent) {
         Ok(cmd) => cmd,
         Err(e) => {
             eprintln!("Command Build Error: {}", e);
@@ -108,35 +329,121 @@ fn process_command(command: &str, dry_run: bool, output: Option<String>) -> Resu
         }
     };
 
-    let final_cmd = if let Some(output_dir) = output {
-        let output_path = std::path::PathBuf::from(&output_dir)
+    if let Some(output_dir) = output_dir {
+        let output_path = std::path::PathBuf::from(output_dir)
             .join(intent.output_path.file_name().ok_or("Invalid output path")?);
 
-        match cmd_builder.build_command_with_output_path(&intent, output_path) {
-            Ok(cmd) => cmd,
+        match cmd_builder.build_command_with_output_path(intent, output_path) {
+            Ok(cmd) => Ok(cmd),
             Err(e) => {
                 eprintln!("Command Build Error: {}", e);
                 eprintln!("Guidance: Check that your output directory is valid and writable.");
-                return Err(e);
+                Err(e)
             }
         }
     } else {
-        ffmpeg_cmd
-    };
+        Ok(ffmpeg_cmd)
+    }
+}
 
-    println!("{}", final_cmd);
+/// Runs every intent of a batch on the job queue and prints a summary table at the end.
+///
+/// With a single worker each job shows a progress bar; with several, a line
+/// is printed as each job starts and ends.
+fn run_batch(intents: Vec<Intent>, settings: &Settings) -> Result<i32, Box<dyn std::error::Error>> {
+    let total = intents.len();
+    let mut jobs = Vec::new();
+    let mut not_run = Vec::new();
 
-    if !dry_run {
-        let runner = Runner::new();
-        match runner.execute(&final_cmd) {
-            Ok(_) => {},
+    for intent in intents {
+        let input = intent.input_path().map(|path| path.display().to_string()).unwrap_or_default();
+
+        if intent.operation == Operation::Inspect {
+            println!("{}", input);
+            match inspect_media(&intent, settings.dry_run) {
+                Ok(_) => not_run.push((input, JobStatus::Succeeded)),
+                Err(e) => not_run.push((input, JobStatus::Failed(e.to_string()))),
+            }
+            continue;
+        }
+
+        let command = match prepare_command(&intent, settings.output.as_deref()) {
+            Ok(command) => command,
             Err(e) => {
-                eprintln!("Execution Error: {}", e);
-                eprintln!("Guidance: Make sure ffmpeg is installed and accessible in your PATH.");
-                return Err(Box::new(e));
+                not_run.push((input, JobStatus::Failed(e.to_string())));
+                continue;
+            },
+        };
+        let output_path = command.get_output().map(|path| path.display().to_string()).unwrap_or_default();
+        let label = format!("{} -> {}", input, output_path);
+
+        match settings.runner.resolve_output(&command) {
+            Ok(command) => jobs.push(Job { label, duration: probe::expected_duration(&intent), command }),
+            Err(ExecutionError::OutputExists(_)) => {
+                not_run.push((label, JobStatus::Skipped("output already exists".to_string())))
+            },
+            Err(e) => not_run.push((label, JobStatus::Failed(e.to_string()))),
+        }
+    }
+
+    if settings.dry_run {
+        for job in &jobs {
+            if settings.explain {
+                println!("{}", job.command.explained());
+            } else {
+                println!("{}", job.command);
             }
         }
+        return Ok(0);
     }
 
-    Ok(())
+    let runner = &settings.runner;
+    let sequential = settings.queue.workers() == 1;
+    let execute = |job: &Job| {
+        if sequential {
+            let mut reporter = ConsoleProgress::new(io::stdout(), io::stdout().is_terminal());
+            runner.execute_with_progress(&job.command, job.duration, &mut reporter)
+        } else {
+            runner.execute_with_progress(&job.command, job.duration, &mut |_: &Progress| {})
+        }
+    };
+    let on_event = |event: JobEvent<'_>| match event {
+        JobEvent::Started { index, job } => println!("[{}/{}] {}\n{}", index + 1, total, job.label, job.command),
+        JobEvent::Finished { index, job, status } => {
+            if !sequential || *status != JobStatus::Succeeded {
+                println!("[{}/{}] {}: {}", index + 1, total, job.label, status);
+            }
+        },
+    };
+
+    let mut summary = settings.queue.run_with(&jobs, execute, on_event);
+    for (input, status) in not_run {
+        summary.record(input, status);
+    }
+
+    println!();
+    println!("{}", summary);
+
+    Ok(summary.exit_code())
+}
+
+fn inspect_media(intent: &Intent, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let input_path = intent.input_path().ok_or("No file to inspect")?;
+
+    if dry_run {
+        println!("{}", MediaInfo::probe_command(input_path));
+        return Ok(());
+    }
+
+    match MediaInfo::probe(input_path) {
+        Ok(info) => {
+            println!("{}", info);
+            Ok(())
+        },
+        Err(e) => {
+            eprintln!("Inspection Error: {}", e);
+            eprintln!("Guidance: Make sure ffprobe (part of ffmpeg) is installed and the file exists.");
+            Err(Box::new(e))
+        }
+    }
 }
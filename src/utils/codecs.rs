@@ -0,0 +1,115 @@
+/// The kind of stream a codec encodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CodecKind {
+    /// A video codec
+    Video,
+    /// An audio codec
+    Audio,
+}
+
+/// A codec users can refer to by a friendly name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Codec {
+    /// The friendly name shown to users (e.g., `h265`)
+    pub name: &'static str,
+    /// The ffmpeg encoder that implements the codec (e.g., `libx265`)
+    pub encoder: &'static str,
+    /// Whether the codec is for video or audio
+    pub kind: CodecKind,
+}
+
+const fn codec(name: &'static str, encoder: &'static str, kind: CodecKind) -> Codec {
+    Codec { name, encoder, kind }
+}
+
+/// Known codecs, keyed by friendly name. Aliases follow their canonical entry.
+const CODECS: &[Codec] = &[
+    codec("h264", "libx264", CodecKind::Video),
+    codec("avc", "libx264", CodecKind::Video),
+    codec("x264", "libx264", CodecKind::Video),
+    codec("h265", "libx265", CodecKind::Video),
+    codec("hevc", "libx265", CodecKind::Video),
+    codec("x265", "libx265", CodecKind::Video),
+    codec("av1", "libaom-av1", CodecKind::Video),
+    codec("svt-av1", "libsvtav1", CodecKind::Video),
+    codec("svtav1", "libsvtav1", CodecKind::Video),
+    codec("vp9", "libvpx-vp9", CodecKind::Video),
+    codec("vp8", "libvpx", CodecKind::Video),
+    codec("mpeg4", "mpeg4", CodecKind::Video),
+    codec("wmv2", "wmv2", CodecKind::Video),
+    codec("aac", "aac", CodecKind::Audio),
+    codec("opus", "libopus", CodecKind::Audio),
+    codec("mp3", "libmp3lame", CodecKind::Audio),
+    codec("vorbis", "libvorbis", CodecKind::Audio),
+    codec("flac", "flac", CodecKind::Audio),
+    codec("pcm", "pcm_s16le", CodecKind::Audio),
+    codec("wmav2", "wmav2", CodecKind::Audio),
+];
+
+/// Looks up a codec by friendly name or by ffmpeg encoder name.
+///
+/// # Arguments
+///
+/// * `name` - The codec name, matched case-insensitively
+///
+/// # Returns
+///
+/// The matching `Codec`, or `None` if the name is unknown.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::utils::codecs::lookup_codec;
+/// assert_eq!(lookup_codec("hevc").unwrap().encoder, "libx265");
+/// assert_eq!(lookup_codec("libopus").unwrap().name, "opus");
+/// assert!(lookup_codec("divx").is_none());
+/// ```
+pub fn lookup_codec(name: &str) -> Option<Codec> {
+    let name = name.to_lowercase();
+    CODECS.iter()
+        .find(|codec| codec.name == name)
+        .or_else(|| CODECS.iter().find(|codec| codec.encoder == name))
+        .copied()
+}
+
+/// Writes a codec as its friendly name, e.g. `"h265"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Codec {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+/// Reads a codec from any name `lookup_codec` accepts.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Codec {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        lookup_codec(&name).ok_or_else(|| serde::de::Error::custom(format!("unknown codec: {}", name)))
+    }
+}
+
+/// Returns the friendly names of all known codecs, in table order.
+pub fn known_codec_names() -> Vec<&'static str> {
+    CODECS.iter().map(|codec| codec.name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_codec() {
+        assert_eq!(lookup_codec("h264").unwrap().encoder, "libx264");
+        assert_eq!(lookup_codec("HEVC").unwrap().encoder, "libx265");
+        assert_eq!(lookup_codec("av1").unwrap().encoder, "libaom-av1");
+        assert_eq!(lookup_codec("svt-av1").unwrap().encoder, "libsvtav1");
+        assert_eq!(lookup_codec("vp9").unwrap().encoder, "libvpx-vp9");
+        assert_eq!(lookup_codec("opus").unwrap().kind, CodecKind::Audio);
+        assert_eq!(lookup_codec("mp3").unwrap().encoder, "libmp3lame");
+        assert_eq!(lookup_codec("pcm_s16le").unwrap().name, "pcm");
+        assert!(lookup_codec("divx").is_none());
+    }
+}
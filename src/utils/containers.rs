@@ -0,0 +1,230 @@
+use crate::utils::codecs::{lookup_codec, Codec, CodecKind};
+
+/// A container format and the codecs it can hold, in order of preference.
+///
+/// Codecs are listed by friendly name. The first of each list is what a
+/// transcode uses when no codec of that kind is asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Container {
+    /// The file extensions of the container, the usual one first
+    pub extensions: &'static [&'static str],
+    /// The video codecs the container holds; empty for audio-only containers
+    pub video: &'static [&'static str],
+    /// The audio codecs the container holds
+    pub audio: &'static [&'static str],
+    /// The subtitle formats the container holds, by ffmpeg encoder name
+    pub subtitle: &'static [&'static str],
+}
+
+const fn container(
+    extensions: &'static [&'static str],
+    video: &'static [&'static str],
+    audio: &'static [&'static str],
+    subtitle: &'static [&'static str],
+) -> Container {
+    Container { extensions, video, audio, subtitle }
+}
+
+/// Containers whose codecs are checked: every audio and video format files
+/// are written as. Image formats are not listed; ffmpeg picks their encoder.
+const CONTAINERS: &[Container] = &[
+    container(&["mp4", "m4v"], &["h264", "h265", "av1", "vp9", "mpeg4"], &["aac", "mp3", "opus", "flac"], &["mov_text"]),
+    container(&["mov"], &["h264", "h265", "mpeg4"], &["aac", "mp3", "pcm"], &["mov_text"]),
+    container(
+        &["mkv"],
+        &["h264", "h265", "av1", "vp9", "vp8", "mpeg4"],
+        &["aac", "opus", "vorbis", "mp3", "flac", "pcm"],
+        &["srt", "ass", "webvtt"],
+    ),
+    container(&["webm"], &["vp9", "vp8", "av1"], &["opus", "vorbis"], &["webvtt"]),
+    container(&["avi"], &["mpeg4", "h264"], &["mp3", "aac", "pcm"], &[]),
+    container(&["wmv"], &["wmv2", "h264", "mpeg4"], &["wmav2", "mp3", "aac"], &[]),
+    container(&["mp3"], &[], &["mp3"], &[]),
+    container(&["m4a"], &[], &["aac"], &[]),
+    container(&["aac"], &[], &["aac"], &[]),
+    container(&["opus"], &[], &["opus"], &[]),
+    container(&["ogg", "oga"], &[], &["vorbis", "opus", "flac"], &[]),
+    container(&["flac"], &[], &["flac"], &[]),
+    container(&["wav"], &[], &["pcm"], &[]),
+    container(&["mka"], &[], &["aac", "opus", "vorbis", "mp3", "flac", "pcm"], &[]),
+];
+
+/// Video containers offered as alternatives when codecs do not fit, in order.
+const ALTERNATIVES: &[&str] = &["mp4", "webm", "mkv"];
+
+impl Container {
+    /// Returns the container's usual extension, e.g. `mp4`.
+    pub fn name(&self) -> &'static str {
+        self.extensions[0]
+    }
+
+    /// Returns the codecs of one kind that the container holds.
+    pub fn codecs(&self, kind: CodecKind) -> &'static [&'static str] {
+        match kind {
+            CodecKind::Video => self.video,
+            CodecKind::Audio => self.audio,
+        }
+    }
+
+    /// Returns whether the container can hold the given codec.
+    pub fn holds(&self, codec: Codec) -> bool {
+        self.codecs(codec.kind).contains(&format_name(codec))
+    }
+
+    /// Returns the codec of one kind a transcode uses when none is asked for,
+    /// or `None` when the container holds no stream of that kind.
+    pub fn preferred(&self, kind: CodecKind) -> Option<Codec> {
+        self.codecs(kind).first().and_then(|name| lookup_codec(name))
+    }
+}
+
+/// Looks up a container by file extension.
+///
+/// # Arguments
+///
+/// * `extension` - The extension without the dot, matched case-insensitively
+///
+/// # Returns
+///
+/// The matching `Container`, or `None` for formats whose codecs are not checked.
+pub fn lookup_container(extension: &str) -> Option<&'static Container> {
+    let extension = extension.to_lowercase();
+    CONTAINERS.iter().find(|container| container.extensions.contains(&extension.as_str()))
+}
+
+/// Checks that a container can hold the requested codecs.
+///
+/// # Arguments
+///
+/// * `extension` - The output's extension without the dot
+/// * `video` - The requested video codec, if any
+/// * `audio` - The requested audio codec, if any
+///
+/// # Returns
+///
+/// `Ok` when every codec fits or the container is not in the table, or an
+/// error message naming what the container needs, the closest combination
+/// that fits, and other containers that hold the codecs as asked.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::utils::codecs::lookup_codec;
+/// use ffrs::utils::containers::check_codecs;
+/// let (h264, aac) = (lookup_codec("h264"), lookup_codec("aac"));
+/// assert!(check_codecs("mp4", h264, aac).is_ok());
+/// let error = check_codecs("webm", h264, aac).unwrap_err();
+/// assert!(error.contains("webm needs vp9/vp8/av1 + opus/vorbis"));
+/// assert!(error.contains("using vp9 and opus"));
+/// ```
+pub fn check_codecs(extension: &str, video: Option<Codec>, audio: Option<Codec>) -> Result<(), String> {
+    let Some(container) = lookup_container(extension) else {
+        return Ok(());
+    };
+
+    let misfits: Vec<String> = [video, audio].into_iter()
+        .flatten()
+        .filter(|codec| !container.holds(*codec))
+        .map(|codec| match codec.kind {
+            CodecKind::Video if container.video.is_empty() => format!("video ({}); it holds audio only", codec.name),
+            CodecKind::Video => format!("{} video", codec.name),
+            CodecKind::Audio => format!("{} audio", codec.name),
+        })
+        .collect();
+    if misfits.is_empty() {
+        return Ok(());
+    }
+
+    let mut message = format!("A .{} file cannot hold {}", container.name(), misfits.join(" or "));
+
+    if !container.video.is_empty() {
+        message.push_str(&format!(
+            "; {} needs {} + {}",
+            container.name(),
+            container.video.join("/"),
+            container.audio.join("/")
+        ));
+    }
+
+    let closest: Vec<&str> = [(video, CodecKind::Video), (audio, CodecKind::Audio)].into_iter()
+        .filter_map(|(codec, kind)| match codec {
+            Some(codec) if container.holds(codec) => Some(codec.name),
+            Some(_) => container.preferred(kind).map(|codec| codec.name),
+            None => None,
+        })
+        .collect();
+    let others: Vec<String> = ALTERNATIVES.iter()
+        .filter_map(|name| lookup_container(name))
+        .filter(|other| [video, audio].into_iter().flatten().all(|codec| other.holds(codec)))
+        .map(|other| format!(".{}", other.name()))
+        .collect();
+
+    let mut suggestions = Vec::new();
+    if !closest.is_empty() {
+        suggestions.push(format!("try 'using {}'", closest.join(" and ")));
+    }
+    if !others.is_empty() {
+        suggestions.push(format!("save as {}", others.join(" or ")));
+    }
+    let suggestion = suggestions.join(", or ");
+    if let Some(first) = suggestion.chars().next() {
+        message.push_str(&format!(". {}{}", first.to_uppercase(), &suggestion[first.len_utf8()..]));
+    }
+
+    Err(message)
+}
+
+/// Returns the table name of a codec's format, so that aliases such as
+/// `hevc` and other encoders of one format such as `svt-av1` match it.
+fn format_name(codec: Codec) -> &'static str {
+    match codec.encoder {
+        "libsvtav1" => "av1",
+        encoder => lookup_codec(encoder).map_or(codec.name, |canonical| canonical.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codec(name: &str) -> Option<Codec> {
+        lookup_codec(name)
+    }
+
+    #[test]
+    fn test_containers_hold_their_codecs() {
+        let mp4 = lookup_container("MP4").unwrap();
+        assert!(mp4.holds(codec("hevc").unwrap()));
+        assert!(!mp4.holds(codec("vorbis").unwrap()));
+        assert!(lookup_container("webm").unwrap().holds(codec("svt-av1").unwrap()));
+        assert_eq!(lookup_container("webm").unwrap().preferred(CodecKind::Video), codec("vp9"));
+        assert_eq!(lookup_container("mp3").unwrap().preferred(CodecKind::Video), None);
+        assert_eq!(lookup_container("wav").unwrap().preferred(CodecKind::Audio), codec("pcm"));
+        assert_eq!(lookup_container("wmv").unwrap().preferred(CodecKind::Video), codec("wmv2"));
+        assert!(lookup_container("png").is_none());
+    }
+
+    #[test]
+    fn test_check_codecs_suggests_closest_combination() {
+        assert!(check_codecs("mkv", codec("vp9"), codec("aac")).is_ok());
+        assert!(check_codecs("wav", None, codec("pcm")).is_ok());
+
+        let error = check_codecs("webm", codec("h264"), codec("aac")).unwrap_err();
+        assert_eq!(
+            error,
+            "A .webm file cannot hold h264 video or aac audio; webm needs vp9/vp8/av1 + opus/vorbis. \
+             Try 'using vp9 and opus', or save as .mp4 or .mkv"
+        );
+
+        let error = check_codecs("mp4", codec("vp9"), codec("vorbis")).unwrap_err();
+        assert!(error.contains("Try 'using vp9 and aac', or save as .webm or .mkv"), "{}", error);
+
+        let error = check_codecs("mp3", codec("h264"), None).unwrap_err();
+        assert_eq!(error, "A .mp3 file cannot hold video (h264); it holds audio only. Save as .mp4 or .mkv");
+
+        let error = check_codecs("wav", codec("h264"), None).unwrap_err();
+        assert_eq!(error, "A .wav file cannot hold video (h264); it holds audio only. Save as .mp4 or .mkv");
+        let error = check_codecs("wav", None, codec("aac")).unwrap_err();
+        assert_eq!(error, "A .wav file cannot hold aac audio. Try 'using pcm', or save as .mp4 or .mkv");
+    }
+}
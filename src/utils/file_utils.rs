@@ -1,4 +1,5 @@
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 
 /// Checks if the given file path has a supported media format.
 ///
@@ -20,16 +21,158 @@ use std::path::Path;
 pub fn is_supported_format<P: AsRef<Path>>(file_path: P) -> bool {
     let file_path = file_path.as_ref();
     if let Some(ext) = file_path.extension() {
-        let ext_lower = ext.to_string_lossy().to_lowercase();
-        matches!(ext_lower.as_str(),
-            "mp4" | "avi" | "mov" | "wmv" | "mkv" | "webm" |
-            "mp3" | "wav" | "flac" | "jpg" | "png" | "gif"
-        )
+        is_supported_extension(&ext.to_string_lossy())
     } else {
         false
     }
 }
 
+/// Checks if a file extension, given without the dot, is a supported media format.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::utils::file_utils::is_supported_extension;
+/// assert!(is_supported_extension("MP3"));
+/// assert!(!is_supported_extension("txt"));
+/// ```
+pub fn is_supported_extension(extension: &str) -> bool {
+    SUPPORTED_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+}
+
+/// The media file extensions ffrs reads and writes, without the dot.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "mp4", "avi", "mov", "wmv", "mkv", "webm",
+    "mp3", "wav", "flac", "m4a", "aac", "opus", "ogg", "mka",
+    "jpg", "png", "gif",
+];
+
+/// Checks whether a file name matches a wildcard pattern.
+///
+/// `*` matches any run of characters and `?` matches exactly one. Matching
+/// ignores case, so `*.mov` also finds `CLIP.MOV`.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::utils::file_utils::matches_wildcard;
+/// assert!(matches_wildcard("*.mov", "Clip 01.MOV"));
+/// assert!(matches_wildcard("take?.wav", "take2.wav"));
+/// assert!(!matches_wildcard("*.mov", "clip.mp4"));
+/// ```
+pub fn matches_wildcard(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+
+    // Greedy matching that backtracks to the most recent `*` on a mismatch.
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Lists the files in a directory whose names match a wildcard pattern.
+///
+/// # Arguments
+///
+/// * `directory` - The directory to search; an empty path means the current directory
+/// * `pattern` - A file name pattern as accepted by `matches_wildcard`
+///
+/// # Returns
+///
+/// The matching files, sorted by path, each joined onto `directory`.
+/// Subdirectories are not searched.
+pub fn find_matching_files<P: AsRef<Path>>(directory: P, pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let directory = directory.as_ref();
+    let listing = if directory.as_os_str().is_empty() { Path::new(".") } else { directory };
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(listing)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name();
+        if matches_wildcard(pattern, &name.to_string_lossy()) {
+            files.push(directory.join(name));
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Checks whether two paths name the same file, following symlinks.
+///
+/// A path that does not exist yet is compared by its absolute form, with its
+/// parent directory resolved when that exists, so `./clip.mp4` and
+/// `clip.mp4` match even before the file is created.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::utils::file_utils::is_same_file;
+/// assert!(is_same_file("clip.mp4", "./clip.mp4"));
+/// assert!(!is_same_file("clip.mp4", "clip.avi"));
+/// ```
+pub fn is_same_file<P: AsRef<Path>, Q: AsRef<Path>>(first: P, second: Q) -> bool {
+    match (resolve_path(first.as_ref()), resolve_path(second.as_ref())) {
+        (Some(first), Some(second)) => first == second,
+        _ => false,
+    }
+}
+
+/// Returns the canonical form of a path, or of its parent for a file that does not exist yet.
+fn resolve_path(path: &Path) -> Option<PathBuf> {
+    if let Ok(canonical) = path.canonicalize() {
+        return Some(canonical);
+    }
+
+    let name = path.file_name()?;
+    let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let parent = parent.canonicalize().or_else(|_| std::path::absolute(parent)).ok()?;
+    Some(parent.join(name))
+}
+
+/// Formats a byte count for people, e.g. `12.3 MB`.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::utils::file_utils::format_size;
+/// assert_eq!(format_size(512), "512 B");
+/// assert_eq!(format_size(12_897_484), "12.3 MB");
+/// ```
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,4 +183,41 @@ mod tests {
         assert!(is_supported_format("test.avi"));
         assert!(!is_supported_format("test.txt"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_matches_wildcard() {
+        assert!(matches_wildcard("*", "anything.mp4"));
+        assert!(matches_wildcard("clip_*_final.mp4", "clip_01_final.mp4"));
+        assert!(matches_wildcard("a*b*c", "aXbYbZc"));
+        assert!(!matches_wildcard("clip?.mp4", "clip10.mp4"));
+        assert!(!matches_wildcard("*.mp4", "mp4"));
+    }
+
+    #[test]
+    fn test_find_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["b.mov", "a.MOV", "c.mp4"] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+        std::fs::create_dir(dir.path().join("nested.mov")).unwrap();
+
+        let files = find_matching_files(dir.path(), "*.mov").unwrap();
+        assert_eq!(files, vec![dir.path().join("a.MOV"), dir.path().join("b.mov")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_is_same_file_follows_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let video = dir.path().join("video.mp4");
+        let link = dir.path().join("link.mp4");
+        std::fs::write(&video, b"").unwrap();
+        std::os::unix::fs::symlink(&video, &link).unwrap();
+
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+
+        assert!(is_same_file(&video, &link));
+        assert!(is_same_file(dir.path().join("nested/../video.mp4"), &video));
+        assert!(!is_same_file(&video, dir.path().join("video.avi")));
+    }
+}
@@ -1,3 +1,6 @@
 //! Utility functions for the FF CLI tool.
 
-pub mod file_utils;
\ No newline at end of file
+pub mod codecs;
+pub mod containers;
+pub mod file_utils;
+pub mod time;
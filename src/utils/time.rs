@@ -0,0 +1,101 @@
+/// Parses a clock-style timestamp into seconds.
+///
+/// Accepts `ss`, `mm:ss` and `hh:mm:ss`, each with an optional fractional
+/// part on the seconds (e.g., `1:30`, `00:01:23.5`). Every part is written
+/// in digits, so signs, exponents and words such as `inf` are rejected.
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::utils::time::parse_timestamp;
+/// assert_eq!(parse_timestamp("1:30"), Some(90.0));
+/// assert_eq!(parse_timestamp("01:00:02.5"), Some(3602.5));
+/// assert_eq!(parse_timestamp("1:2:3:4"), None);
+/// assert_eq!(parse_timestamp("-5"), None);
+/// ```
+pub fn parse_timestamp(text: &str) -> Option<f64> {
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+
+    let mut seconds = 0.0;
+    for (index, part) in parts.iter().enumerate() {
+        let is_last = index == parts.len() - 1;
+        let digits = part.bytes().all(|byte| byte.is_ascii_digit() || (is_last && byte == b'.'));
+        if !digits || !part.bytes().any(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        let value: f64 = part.parse().ok()?;
+        if index > 0 && value >= 60.0 {
+            return None;
+        }
+        seconds = seconds * 60.0 + value;
+    }
+
+    Some(seconds)
+}
+
+/// Returns the number of seconds in one unit of a duration word.
+///
+/// Recognizes `ms`, `s`, `m`, `h` and their spelled-out forms
+/// (`seconds`, `mins`, `hour`, ...).
+pub fn unit_seconds(unit: &str) -> Option<f64> {
+    match unit {
+        "ms" | "millisecond" | "milliseconds" => Some(0.001),
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(1.0),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(60.0),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(3600.0),
+        _ => None,
+    }
+}
+
+/// Formats seconds as an ffmpeg timestamp (`HH:MM:SS` or `HH:MM:SS.mmm`).
+///
+/// # Examples
+///
+/// ```
+/// use ffrs::utils::time::format_timestamp;
+/// assert_eq!(format_timestamp(90.0), "00:01:30");
+/// assert_eq!(format_timestamp(3602.25), "01:00:02.250");
+/// ```
+pub fn format_timestamp(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    let (hours, rest) = (millis / 3_600_000, millis % 3_600_000);
+    let (minutes, rest) = (rest / 60_000, rest % 60_000);
+    let (secs, millis) = (rest / 1000, rest % 1000);
+
+    if millis == 0 {
+        format!("{:02}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, secs, millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("45"), Some(45.0));
+        assert_eq!(parse_timestamp("2:45"), Some(165.0));
+        assert_eq!(parse_timestamp("00:01:23.5"), Some(83.5));
+        assert_eq!(parse_timestamp("1:75"), None);
+        assert_eq!(parse_timestamp("1.5:30"), None);
+        assert_eq!(parse_timestamp(":30"), None);
+        assert_eq!(parse_timestamp("inf"), None);
+        assert_eq!(parse_timestamp("NaN"), None);
+        assert_eq!(parse_timestamp("-5"), None);
+        assert_eq!(parse_timestamp("1:-30"), None);
+        assert_eq!(parse_timestamp("1e3"), None);
+        assert_eq!(parse_timestamp("."), None);
+    }
+
+    #[test]
+    fn test_format_timestamp_round_trips() {
+        for seconds in [0.0, 5.0, 90.0, 165.5, 3723.125] {
+            assert_eq!(parse_timestamp(&format_timestamp(seconds)), Some(seconds));
+        }
+    }
+}
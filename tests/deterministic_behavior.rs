@@ -31,5 +31,5 @@ fn process_command(command: &str) -> String {
 
     // Build the ffmpeg command
     let cmd_builder = CommandBuilder::new();
-    cmd_builder.build_command(&intent).expect("Command building should succeed")
+    cmd_builder.build_command(&intent).expect("Command building should succeed").to_string()
 }
\ No newline at end of file
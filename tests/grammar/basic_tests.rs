@@ -30,7 +30,7 @@ mod grammar_tests {
         let intent = parser.parse().unwrap();
         
         assert_eq!(intent.operation, OperationType::Convert);
-        assert_eq!(intent.input_path, PathBuf::from("input.mp4"));
+        assert_eq!(intent.input_paths, vec![PathBuf::from("input.mp4")]);
         assert_eq!(intent.output_path, PathBuf::from("output.avi"));
     }
 
@@ -47,7 +47,7 @@ mod grammar_tests {
         let intent = parser.parse().unwrap();
         
         assert_eq!(intent.operation, OperationType::Convert);
-        assert_eq!(intent.input_path, PathBuf::from("input.mp4"));
+        assert_eq!(intent.input_paths, vec![PathBuf::from("input.mp4")]);
         assert_eq!(intent.output_path, PathBuf::from("input.avi"));
     }
 
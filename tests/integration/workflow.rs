@@ -15,7 +15,7 @@ fn test_complete_workflow() {
     
     // Build the ffmpeg command
     let cmd_builder = CommandBuilder::new();
-    let ffmpeg_cmd = cmd_builder.build_command(&intent).expect("Command building should succeed");
+    let ffmpeg_cmd = cmd_builder.build_command(&intent).expect("Command building should succeed").to_string();
     
     // Verify the command structure
     assert!(ffmpeg_cmd.starts_with("ffmpeg -i"));